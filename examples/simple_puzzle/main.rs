@@ -15,6 +15,6 @@ fn main() {
 
   let mut search = Bfs::new(state);
 
-  let state = search.next_goal(SimplePuzzle::is_solved).unwrap();
+  let state = search.next_goal(SimplePuzzle::is_solved).unwrap().unwrap();
   println!("{}", state);
 }
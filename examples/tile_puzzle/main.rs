@@ -31,7 +31,7 @@ fn main() {
     },
   );
 
-  let state = search.next_goal(TilePuzzle::is_solved).unwrap();
+  let state = search.next_goal(TilePuzzle::is_solved).unwrap().unwrap();
   println!("{}", state);
   println!("{:?}", state.history());
 }
@@ -1,185 +1,637 @@
-use crate::prelude::{SearchHeuristic, State};
+//! The alpha-beta algorithm.
+//!
+//! This finds the same value as minimax, but stops searching the actions from
+//! a state once it is clear that the other player would never allow it to be
+//! reached.
+
+use super::{
+  ordering::to_front, Entry, GameStats, MoveOrdering, TranspositionTable, Transpositions,
+  ValueBound,
+};
+use crate::{
+  prelude::{Game, SearchHeuristic, State},
+  timer::Timed,
+};
 use std::{
-  fmt::Debug,
   marker::PhantomData,
-  ops::{Bound, RangeBounds},
+  ops::{Bound, RangeBounds, Sub},
+  time::{Duration, Instant},
 };
 
-/** A wrapper struct to enable comparison of bounds
- * @warn bounds don't really have a good concept of comparison
- */
-pub struct ComparableBound<O>(Bound<O>);
+/// The lower and upper bounds of a window of values
+type Window<C> = (Bound<C>, Bound<C>);
 
-impl<O: PartialEq + Debug> PartialEq<O> for ComparableBound<O> {
-  /** @todo I'm pretty sure this is wrong */
-  fn eq(&self, other: &O) -> bool {
-    match &self.0 {
-      Bound::Unbounded => false,
-      Bound::Included(x) => x == other,
-      Bound::Excluded(x) => x != other,
-    }
-  }
-}
+/// Gives the window to search around the value of the previous iteration
+type Aspiration<'a, C> = dyn Fn(&C) -> Window<C> + 'a;
 
-impl<O: PartialOrd + Debug> PartialOrd<O> for ComparableBound<O> {
-  /** @todo This might be wrong too, honestly just return None */
-  fn partial_cmp(&self, other: &O) -> Option<std::cmp::Ordering> {
-    use std::cmp::Ordering::*;
-    match &self.0 {
-      Bound::Unbounded => None,
-      Bound::Included(x) => x.partial_cmp(other),
-      Bound::Excluded(x) if x > other => Some(Less),
-      Bound::Excluded(x) if x < other => Some(Greater),
-      Bound::Excluded(_) => None,
-    }
-  }
+/// The actions chosen from a state, and the value of the state they lead to
+type Line<S, C> = (Vec<<S as State>::Action>, C);
 
-  fn gt(&self, other: &O) -> bool {
-    match &self.0 {
-      Bound::Unbounded => true,
-      Bound::Included(x) => other > x,
-      Bound::Excluded(x) => other >= x,
-    }
+/// Whether an item lies above the lower bound of a window
+fn above<O: Ord>(alpha: &Bound<O>, item: &O) -> bool {
+  match alpha {
+    Bound::Unbounded => true,
+    Bound::Included(alpha) => item >= alpha,
+    Bound::Excluded(alpha) => item > alpha,
   }
+}
 
-  fn lt(&self, other: &O) -> bool {
-    match &self.0 {
-      Bound::Unbounded => true,
-      Bound::Included(x) => other < x,
-      Bound::Excluded(x) => other <= x,
-    }
+/// Whether an item lies below the upper bound of a window
+fn below<O: Ord>(beta: &Bound<O>, item: &O) -> bool {
+  match beta {
+    Bound::Unbounded => true,
+    Bound::Included(beta) => item <= beta,
+    Bound::Excluded(beta) => item < beta,
   }
 }
 
-fn ge_bound<O: Ord>(bound: &Bound<O>, item: &O) -> bool {
+/// The same bound, but including the item if it was excluded and vice versa
+fn flip<O>(bound: Bound<O>) -> Bound<O> {
   match bound {
-    Bound::Unbounded => true,
-    Bound::Included(bound) => bound <= item,
-    Bound::Excluded(bound) => bound < item,
+    Bound::Unbounded => Bound::Unbounded,
+    Bound::Included(item) => Bound::Excluded(item),
+    Bound::Excluded(item) => Bound::Included(item),
   }
 }
 
-fn le_bound<O: Ord>(bound: &Bound<O>, item: &O) -> bool {
-  match bound {
-    Bound::Unbounded => true,
-    Bound::Included(bound) => bound >= item,
-    Bound::Excluded(bound) => bound > item,
+/// Whether no item can lie within a window, so searching with it only finds
+/// which side of the window the value lies on
+fn is_zero<O: Ord>((alpha, beta): &Window<O>) -> bool {
+  match (alpha, beta) {
+    (Bound::Excluded(alpha), Bound::Included(beta)) => alpha == beta,
+    (Bound::Included(alpha), Bound::Excluded(beta)) => alpha == beta,
+    _ => false,
   }
 }
 
-fn included<O>(bound: Bound<O>) -> Option<O> {
-  match bound {
-    Bound::Included(item) => Some(item),
-    Bound::Excluded(_) => None,
-    Bound::Unbounded => None,
+/// Whether a window is empty, so that some value lies both below it and above
+/// it. Zero windows aren't empty, as every value still lies on one side.
+fn is_empty<O: Ord>((alpha, beta): &Window<O>) -> bool {
+  match (alpha, beta) {
+    (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+    (Bound::Excluded(alpha), Bound::Excluded(beta)) => alpha >= beta,
+    (
+      Bound::Included(alpha) | Bound::Excluded(alpha),
+      Bound::Included(beta) | Bound::Excluded(beta),
+    ) => alpha > beta,
   }
 }
 
-pub struct AlphaBeta<S, H> {
+/// A minimax search with fail-soft alpha-beta pruning.
+///
+/// As in `MiniMax`, the player to move at the start maximises the value of
+/// states and every other player minimises it. A value within the window given
+/// to `alphabeta_limits` is exact, and has the same principal variation as
+/// minimax would find. A value below the window is only an upper bound on the
+/// true value, and a value above the window is only a lower bound.
+///
+/// With `iterative_deepening`, `depth` is instead the deepest search to try
+/// within the time budget.
+///
+/// Actions are tried in the order given by the move ordering, except that the
+/// principal variation of the last search and then the best action stored in
+/// the transposition table are tried first.
+///
+/// With principal variation search, every action after the first is only
+/// searched with a zero window, to check that it is no better than the best
+/// action so far, and is searched again with the full window if it is. This
+/// allows null-move pruning and late move reductions, which only apply to
+/// states searched with a zero window. Both can miss the best action, trading
+/// accuracy for a deeper search in the same time.
+///
+/// As in `MiniMax`, a quiescence search can be used to keep searching noisy
+/// actions past the depth limit, pruning them with the same window.
+pub struct AlphaBeta<S, H, T = (), O = ()> {
   pub state_value: H,
   pub depth: usize,
-  pub players: usize,
+  /// The number of noisy actions to search past the depth limit, if a
+  /// quiescence search is used
+  pub quiescence: Option<usize>,
+  /// Whether to use principal variation search
+  pub pvs: bool,
+  /// The reduction in depth for the search after a null move, if null-move
+  /// pruning is used
+  pub null_move: Option<usize>,
+  /// The number of actions to search at full depth from a state, and the
+  /// reduction in depth for the rest, if late move reductions are used
+  pub late_move_reductions: Option<(usize, usize)>,
+  table: T,
+  ordering: O,
+  player: usize,
+  deadline: Option<Instant>,
+  stats: GameStats,
   _state: PhantomData<S>,
 }
 
 impl<S, H> AlphaBeta<S, H> {
-  pub fn new(state_value: H, depth: usize, players: usize) -> Self {
+  pub fn new(state_value: H, depth: usize) -> Self {
     Self {
       state_value,
       depth,
-      players,
+      quiescence: None,
+      pvs: false,
+      null_move: None,
+      late_move_reductions: None,
+      table: (),
+      ordering: (),
+      player: 0,
+      deadline: None,
+      stats: GameStats::default(),
+      _state: PhantomData,
+    }
+  }
+}
+
+impl<S: State, H: SearchHeuristic<S>, O> AlphaBeta<S, H, (), O> {
+  /// Stores the values of states in a transposition table, so that a state
+  /// reached again is only searched again if the stored value is for a
+  /// shallower search, or is a bound that doesn't cause a cutoff.
+  ///
+  /// The actions returned stop at the first state found in the table.
+  pub fn with_table(
+    self,
+    table: TranspositionTable<S::Action, H::Cost>,
+  ) -> AlphaBeta<S, H, TranspositionTable<S::Action, H::Cost>, O> {
+    AlphaBeta {
+      state_value: self.state_value,
+      depth: self.depth,
+      quiescence: self.quiescence,
+      pvs: self.pvs,
+      null_move: self.null_move,
+      late_move_reductions: self.late_move_reductions,
+      table,
+      ordering: self.ordering,
+      player: 0,
+      deadline: None,
+      stats: self.stats,
       _state: PhantomData,
     }
   }
 }
 
-impl<S: State, H: SearchHeuristic<S>> AlphaBeta<S, H>
+impl<S, H, T> AlphaBeta<S, H, T> {
+  /// Orders the actions of each state before they are searched, such as with
+  /// `Killers` or `History`.
+  pub fn with_ordering<O>(self, ordering: O) -> AlphaBeta<S, H, T, O> {
+    AlphaBeta {
+      state_value: self.state_value,
+      depth: self.depth,
+      quiescence: self.quiescence,
+      pvs: self.pvs,
+      null_move: self.null_move,
+      late_move_reductions: self.late_move_reductions,
+      table: self.table,
+      ordering,
+      player: 0,
+      deadline: None,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+}
+
+impl<S, H, T, O> AlphaBeta<S, H, T, O> {
+  /// Enables a quiescence search, searching up to the given number of noisy
+  /// actions past the depth limit.
+  pub fn with_quiescence(mut self, depth: usize) -> Self {
+    self.quiescence = Some(depth);
+    self
+  }
+
+  /// Enables principal variation search
+  pub fn with_pvs(mut self) -> Self {
+    self.pvs = true;
+    self
+  }
+
+  /// Enables null-move pruning, using principal variation search, where the
+  /// search after a null move is reduced by the given depth.
+  ///
+  /// A state is pruned if passing the turn would still be too good for the
+  /// player to move, so this is only suitable for games where taking an action
+  /// is almost always better than passing, and `Game::null_move` has to be
+  /// implemented.
+  pub fn with_null_move(mut self, reduction: usize) -> Self {
+    self.pvs = true;
+    self.null_move = Some(reduction);
+    self
+  }
+
+  /// Enables late move reductions, using principal variation search, where
+  /// every action from a state after the first `after` is searched with its
+  /// depth reduced by `reduction`, unless it turns out to be better.
+  pub fn with_late_move_reductions(mut self, after: usize, reduction: usize) -> Self {
+    self.pvs = true;
+    self.late_move_reductions = Some((after, reduction));
+    self
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &GameStats {
+    &self.stats
+  }
+
+  /// The transposition table, if one is used
+  pub fn table(&self) -> &T {
+    &self.table
+  }
+
+  /// The move ordering
+  pub fn ordering(&self) -> &O {
+    &self.ordering
+  }
+}
+
+impl<S: Game<Utility = H::Cost>, H: SearchHeuristic<S>, T, O> AlphaBeta<S, H, T, O>
 where
-  S::Action: Clone,
+  S::Action: Clone + PartialEq,
+  T: Transpositions<S, H::Cost>,
+  O: MoveOrdering<S>,
 {
-  pub fn alphabeta(&self, start: S) -> Option<(Vec<S::Action>, H::Cost)> {
+  /// Searches from the given state, returning the actions expected to be
+  /// taken and the value of the state they lead to, or `None` if no value
+  /// could be found.
+  ///
+  /// Fails if the result of an action or an observation of a state fails.
+  pub fn alphabeta(&mut self, start: S) -> Result<Option<Line<S, H::Cost>>, S::Error> {
     self.alphabeta_limits(start, ..)
   }
 
+  /// Searches from the given state, only finding the exact value if it lies
+  /// within the given window.
+  ///
+  /// Returns `None` without searching if the window is empty, such as
+  /// `(Excluded(a), Excluded(a))`, as a value could then be on both sides of it.
   pub fn alphabeta_limits<R: RangeBounds<H::Cost>>(
-    &self,
+    &mut self,
     start: S,
     alphabeta: R,
-  ) -> Option<(Vec<S::Action>, H::Cost)> {
-    self.alphabeta_recursive(
-      start,
-      vec![],
-      (
-        alphabeta.start_bound().cloned(),
-        alphabeta.end_bound().cloned(),
-      ),
-    )
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    let window = (
+      alphabeta.start_bound().cloned(),
+      alphabeta.end_bound().cloned(),
+    );
+    if is_empty(&window) {
+      return Ok(None);
+    }
+    let started = self.stats.start();
+    let result = self.search(&start, window, &[]);
+    self.stats.finish(started);
+    result
+  }
+
+  /// Searches to a depth of 1, 2, 3, ... up to `depth`, until the time budget
+  /// runs out, returning the result of the deepest search that finished.
+  ///
+  /// Each search tries the actions of the previous one's principal variation
+  /// first. The search to a depth of 1 always finishes, so there is a result
+  /// however small the budget.
+  pub fn iterative_deepening(
+    &mut self,
+    start: S,
+    time_budget: Duration,
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    self.deepen(start, time_budget, None)
+  }
+
+  /// Deepens iteratively as in `iterative_deepening`, but with an aspiration
+  /// window of `delta` either side of the previous search's value.
+  ///
+  /// A narrow window prunes more, but if the value falls outside of it, the
+  /// search has to be repeated with that side of the window unbounded.
+  pub fn iterative_deepening_aspiration(
+    &mut self,
+    start: S,
+    time_budget: Duration,
+    delta: H::Cost,
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error>
+  where
+    H::Cost: Sub<Output = H::Cost>,
+  {
+    let aspiration = |value: &H::Cost| {
+      let (lower, upper) = (value.clone() - delta.clone(), value.clone() + delta.clone());
+      // Included, so that the window isn't empty when `delta` is 0
+      (Bound::Included(lower), Bound::Included(upper))
+    };
+    self.deepen(start, time_budget, Some(&aspiration))
   }
 
+  /// Deepens iteratively, where `aspiration` gives the window around the
+  /// previous search's value to start each search with.
+  fn deepen(
+    &mut self,
+    start: S,
+    time_budget: Duration,
+    aspiration: Option<&Aspiration<H::Cost>>,
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    let started = self.stats.start();
+    let depth = self.depth;
+    let mut best: Option<Line<S, H::Cost>> = None;
+    let mut error = None;
+    'deepen: for iteration in 1..=depth {
+      self.depth = iteration;
+      let pv = best.as_ref().map_or(&[][..], |(pv, _)| pv.as_slice());
+      let mut window = match (&best, aspiration) {
+        (Some((_, value)), Some(aspiration)) => aspiration(value),
+        _ => (Bound::Unbounded, Bound::Unbounded),
+      };
+      let result = loop {
+        let result = match self.search(&start, window.clone(), pv) {
+          Ok(result) => result,
+          Err(e) => {
+            error = Some(e);
+            break 'deepen;
+          }
+        };
+        if result.is_none() && self.out_of_time() {
+          break 'deepen;
+        }
+        let Some((_, value)) = &result else {
+          break result;
+        };
+        match (above(&window.0, value), below(&window.1, value)) {
+          (false, _) => window.0 = Bound::Unbounded,
+          (_, false) => window.1 = Bound::Unbounded,
+          (true, true) => break result,
+        }
+      };
+      best = result.or(best);
+      self.stats.depth = iteration;
+      self.deadline = Some(started + time_budget);
+    }
+    self.depth = depth;
+    self.deadline = None;
+    self.stats.finish(started);
+    match error {
+      Some(e) => Err(e),
+      None => Ok(best),
+    }
+  }
+
+  fn search(
+    &mut self,
+    start: &S,
+    window: Window<H::Cost>,
+    pv: &[S::Action],
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    self.player = start.player();
+    self.table.start(self.player);
+    self.alphabeta_recursive(start, vec![], window, pv, self.depth, true)
+  }
+
+  /// Finds the value of a state, where `pv` is the rest of the principal
+  /// variation to try first, if the state is on it, and `remaining` is the
+  /// number of actions left to search. `null` is whether a null move may be
+  /// tried, which it can't be straight after another.
+  ///
+  /// Returns `None` if the deadline passes, so the search can be abandoned,
+  /// or if no value could be found for the state.
   fn alphabeta_recursive(
-    &self,
-    state: S,
+    &mut self,
+    state: &S,
     actions: Vec<S::Action>,
-    (alpha, beta): (Bound<H::Cost>, Bound<H::Cost>),
-  ) -> Option<(Vec<S::Action>, H::Cost)> {
-    let turn = actions.len();
-    if turn == self.depth {
-      let observed = state.observe().ok()?;
-      let value = self.state_value.value(&observed);
-      return Some((actions, value));
+    window: Window<H::Cost>,
+    pv: &[S::Action],
+    remaining: usize,
+    null: bool,
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    if self.out_of_time() {
+      return Ok(None);
+    }
+    self.stats.visited += 1;
+    if state.is_terminal() {
+      let value = state.utility().into_iter().nth(self.player);
+      return Ok(value.map(|value| (actions, value)));
+    }
+    if remaining == 0 {
+      let depth = self.quiescence.unwrap_or(0);
+      return self.quiesce(state, actions, window, depth);
+    }
+    let mut hash_action = None;
+    if let Some(entry) = self.table.probe(state) {
+      let (alpha, beta) = &window;
+      let usable = entry.depth >= remaining
+        && match entry.bound {
+          ValueBound::Exact => true,
+          ValueBound::Lower => !below(beta, &entry.value),
+          ValueBound::Upper => !above(alpha, &entry.value),
+        };
+      if usable {
+        self.stats.transpositions += 1;
+        let mut actions = actions;
+        actions.extend(entry.action.clone());
+        return Ok(Some((actions, entry.value.clone())));
+      }
+      hash_action = entry.action.clone();
     }
+    let maximising = state.player() == self.player;
 
-    let paths = state.actions().into_iter().filter_map(|action| {
-      let state = state.result(&action).ok()?;
-      let mut actions = actions.clone();
-      actions.push(action);
-      Some((actions, state))
-    });
+    // If passing the turn is still enough to reach the bound on the other side
+    // of the window, then taking an action would be too
+    if let Some(reduction) = self.null_move.filter(|_| null && is_zero(&window)) {
+      if let Some(child) = state.null_move() {
+        let depth = remaining.saturating_sub(reduction + 1);
+        let result =
+          self.alphabeta_recursive(&child, actions.clone(), window.clone(), &[], depth, false)?;
+        if self.out_of_time() {
+          return Ok(None);
+        }
+        if let Some((_, value)) = result {
+          let (alpha, beta) = &window;
+          if maximising && !below(beta, &value) || !maximising && !above(alpha, &value) {
+            self.stats.cutoffs += 1;
+            return Ok(Some((actions, value)));
+          }
+        }
+      }
+    }
 
-    if turn % self.players == 0 {
-      self.alphabeta_max(paths, (alpha, beta), actions.clone())
-    } else {
-      self.alphabeta_min(paths, (alpha, beta), actions.clone())
+    let ply = actions.len();
+    let mut ordered = state.actions().into_iter().collect::<Vec<_>>();
+    self.ordering.order(state, ply, &mut ordered);
+    if let Some(action) = &hash_action {
+      to_front(&mut ordered, action);
     }
-  }
+    let on_pv = pv
+      .first()
+      .is_some_and(|action| to_front(&mut ordered, action));
 
-  fn alphabeta_min(
-    &self,
-    paths: impl Iterator<Item = (Vec<S::Action>, S)>,
-    (alpha, mut beta): (Bound<H::Cost>, Bound<H::Cost>),
-    actions: Vec<S::Action>,
-  ) -> Option<(Vec<S::Action>, H::Cost)> {
-    for (actions, state) in paths {
-      if let Some((actions, value)) =
-        self.alphabeta_recursive(state, actions, (alpha.clone(), beta.clone()))
-      {
-        if le_bound(&alpha, &value) {
-          return Some((actions, value));
-        } else if le_bound(&beta, &value) {
-          beta = Bound::Included(value);
+    let (mut alpha, mut beta) = window.clone();
+    let mut best: Option<Line<S, H::Cost>> = None;
+    for (index, action) in ordered.into_iter().enumerate() {
+      let child = state.result(&action)?;
+      let mut actions = actions.clone();
+      actions.push(action);
+      let full = (alpha.clone(), beta.clone());
+      let pv = match (index, on_pv) {
+        (0, true) => &pv[1..],
+        _ => &[],
+      };
+
+      // With a zero window on the side the player to move wants to improve,
+      // the search only finds whether the action is any better
+      let zero = match maximising {
+        true => (alpha.clone(), flip(alpha.clone())),
+        false => (flip(beta.clone()), beta.clone()),
+      };
+      let raises = |value: &H::Cost| match maximising {
+        true => above(&alpha, value),
+        false => below(&beta, value),
+      };
+      let mut result = None;
+      if self.pvs && index > 0 && is_zero(&zero) {
+        let late = (self.late_move_reductions)
+          .filter(|(after, reduction)| index >= *after && remaining > reduction + 1);
+        if let Some((_, reduction)) = late {
+          let depth = remaining - reduction - 1;
+          result =
+            self.alphabeta_recursive(&child, actions.clone(), zero.clone(), pv, depth, true)?;
+          result = result.filter(|(_, value)| !raises(value));
+        }
+        if result.is_none() && !self.out_of_time() {
+          let depth = remaining - 1;
+          result = self.alphabeta_recursive(&child, actions.clone(), zero, pv, depth, true)?;
+          let (alpha, beta) = &full;
+          result = result.filter(|(_, value)| !(above(alpha, value) && below(beta, value)));
         }
       }
+      if result.is_none() && !self.out_of_time() {
+        result = self.alphabeta_recursive(&child, actions, full, pv, remaining - 1, true)?;
+      }
+      if self.out_of_time() {
+        return Ok(None);
+      }
+      let Some((path, value)) = result else {
+        continue;
+      };
+      let improved = best.as_ref().is_none_or(|(_, best)| match maximising {
+        true => value > *best,
+        false => value < *best,
+      });
+      if !improved {
+        continue;
+      }
+
+      // Values outside the window can't be reached, so the rest are skipped
+      if maximising && !below(&beta, &value) || !maximising && !above(&alpha, &value) {
+        self.stats.cutoffs += 1;
+        self.ordering.cutoff(state, ply, remaining, &path[ply]);
+        best = Some((path, value));
+        break;
+      }
+      if maximising && above(&alpha, &value) {
+        alpha = Bound::Excluded(value.clone());
+      } else if !maximising && below(&beta, &value) {
+        beta = Bound::Excluded(value.clone());
+      }
+      best = Some((path, value));
     }
-    included(beta).map(|value| (actions, value))
+
+    if let Some((path, value)) = &best {
+      let (alpha, beta) = &window;
+      let bound = match (above(alpha, value), below(beta, value)) {
+        (false, _) => ValueBound::Upper,
+        (_, false) => ValueBound::Lower,
+        (true, true) => ValueBound::Exact,
+      };
+      let action = match bound {
+        ValueBound::Upper => None,
+        _ => path.get(ply).cloned(),
+      };
+      let entry = Entry {
+        depth: remaining,
+        value: value.clone(),
+        bound,
+        action,
+      };
+      self.table.store(state, entry);
+    }
+    Ok(best)
   }
 
-  fn alphabeta_max(
-    &self,
-    paths: impl Iterator<Item = (Vec<S::Action>, S)>,
-    (mut alpha, beta): (Bound<H::Cost>, Bound<H::Cost>),
+  /// Finds the value of a state at the depth limit, searching up to `depth`
+  /// more noisy actions.
+  ///
+  /// The player to move may stand pat rather than take a noisy action, so the
+  /// value of the state itself is a bound on its value, which may be enough
+  /// for a cutoff.
+  fn quiesce(
+    &mut self,
+    state: &S,
     actions: Vec<S::Action>,
-  ) -> Option<(Vec<S::Action>, H::Cost)> {
-    for (actions, state) in paths {
-      if let Some((actions, value)) =
-        self.alphabeta_recursive(state, actions, (alpha.clone(), beta.clone()))
-      {
-        if ge_bound(&beta, &value) {
-          return Some((actions, value));
-        } else if ge_bound(&alpha, &value) {
-          alpha = Bound::Included(value);
-        }
+    window: Window<H::Cost>,
+    depth: usize,
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    let observed = state.observe()?;
+    let stand_pat = self.state_value.value(&observed);
+    if depth == 0 {
+      return Ok(Some((actions, stand_pat)));
+    }
+    let maximising = state.player() == self.player;
+
+    let (mut alpha, mut beta) = window;
+    if maximising && !below(&beta, &stand_pat) || !maximising && !above(&alpha, &stand_pat) {
+      self.stats.cutoffs += 1;
+      return Ok(Some((actions, stand_pat)));
+    }
+    if maximising && above(&alpha, &stand_pat) {
+      alpha = Bound::Excluded(stand_pat.clone());
+    } else if !maximising && below(&beta, &stand_pat) {
+      beta = Bound::Excluded(stand_pat.clone());
+    }
+
+    let ply = actions.len();
+    let noisy = state
+      .actions()
+      .into_iter()
+      .filter(|action| state.is_noisy(action));
+    let mut noisy = noisy.collect::<Vec<_>>();
+    self.ordering.order(state, ply, &mut noisy);
+
+    let mut best = (actions.clone(), stand_pat);
+    for action in noisy {
+      let child = state.result(&action)?;
+      let mut actions = actions.clone();
+      actions.push(action);
+      if self.out_of_time() {
+        return Ok(None);
+      }
+      self.stats.visited += 1;
+      let result = match child.is_terminal() {
+        true => (child.utility().into_iter().nth(self.player)).map(|value| (actions, value)),
+        false => self.quiesce(&child, actions, (alpha.clone(), beta.clone()), depth - 1)?,
+      };
+      if self.out_of_time() {
+        return Ok(None);
       }
+      let Some((path, value)) = result else {
+        continue;
+      };
+      let improved = match maximising {
+        true => value > best.1,
+        false => value < best.1,
+      };
+      if !improved {
+        continue;
+      }
+
+      if maximising && !below(&beta, &value) || !maximising && !above(&alpha, &value) {
+        self.stats.cutoffs += 1;
+        return Ok(Some((path, value)));
+      }
+      if maximising && above(&alpha, &value) {
+        alpha = Bound::Excluded(value.clone());
+      } else if !maximising && below(&beta, &value) {
+        beta = Bound::Excluded(value.clone());
+      }
+      best = (path, value);
     }
-    included(alpha).map(|value| (actions, value))
+    Ok(Some(best))
+  }
+
+  fn out_of_time(&self) -> bool {
+    self
+      .deadline
+      .is_some_and(|deadline| Instant::now() >= deadline)
   }
 }
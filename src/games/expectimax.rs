@@ -0,0 +1,216 @@
+//! The expectimax algorithm.
+//!
+//! This extends minimax to games with chance nodes, where the value of a chance
+//! node is the expected value of its outcomes. With bounds on the values of
+//! states, this becomes Ballen's *-minimax, which prunes chance nodes too.
+
+use super::GameStats;
+use crate::{
+  prelude::SearchHeuristic,
+  state::{Chance, Game, State},
+  timer::Timed,
+};
+use std::marker::PhantomData;
+
+/// The actions chosen from a state, and the value of the state
+type Line<S> = (Vec<<S as State>::Action>, f64);
+
+/// An expectimax search, with optional *-minimax pruning.
+///
+/// As in `MiniMax`, the player to move at the start maximises the value of
+/// states and every other player minimises it. Actions taken by chance count
+/// towards the depth.
+///
+/// Without bounds, every state up to the depth is visited. Given bounds that
+/// every value of `state_value` lies within, the search prunes with alpha-beta
+/// at player nodes and Ballen's Star1 at chance nodes, which stops searching
+/// the outcomes of a chance node once the bounds show its expected value can't
+/// change the result.
+pub struct Expectimax<S, H> {
+  /// The value of a state to the player to move at the start, used at the
+  /// depth limit
+  pub state_value: H,
+  /// The number of actions to look ahead, including those taken by chance
+  pub depth: usize,
+  /// The lowest and highest values `state_value` can give, if known
+  pub bounds: Option<(f64, f64)>,
+  stats: GameStats,
+  _state: PhantomData<S>,
+}
+
+impl<S, H> Expectimax<S, H> {
+  /// Creates a new search, without pruning
+  pub fn new(state_value: H, depth: usize) -> Self {
+    Self {
+      state_value,
+      depth,
+      bounds: None,
+      stats: GameStats::default(),
+      _state: PhantomData,
+    }
+  }
+
+  /// Enables *-minimax pruning, given the lowest and highest values that
+  /// `state_value` can give.
+  pub fn with_bounds(mut self, lower: f64, upper: f64) -> Self {
+    self.bounds = Some((lower, upper));
+    self
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &GameStats {
+    &self.stats
+  }
+}
+
+impl<S: Chance + Game<Utility = H::Cost>, H: SearchHeuristic<S>> Expectimax<S, H>
+where
+  S::Action: Clone,
+  H::Cost: Into<f64>,
+{
+  /// Searches from the given state, returning the actions the players are
+  /// expected to choose up until the first chance node, and the value of the
+  /// state.
+  ///
+  /// Returns `None` if no value could be found, such as when a utility is
+  /// missing for the player.
+  pub fn expectimax(&mut self, start: S) -> Result<Option<Line<S>>, S::Error> {
+    let started = self.stats.start();
+    let window = match self.bounds {
+      Some((lower, upper)) => (lower, upper),
+      None => (f64::NEG_INFINITY, f64::INFINITY),
+    };
+    let player = start.player();
+    let result = self.expectimax_recursive(&start, 0, player, window);
+    self.stats.finish(started);
+    Ok(result?.map(|(mut actions, value)| {
+      actions.reverse();
+      (actions, value)
+    }))
+  }
+
+  /// Finds the value of a state, with the actions chosen from it in reverse.
+  ///
+  /// With bounds, values outside of the window `(alpha, beta)` may only be a
+  /// bound on the true value, in the direction of the window they lie outside.
+  /// Any actions whose values can't be found are skipped.
+  fn expectimax_recursive(
+    &mut self,
+    state: &S,
+    depth: usize,
+    player: usize,
+    (alpha, beta): (f64, f64),
+  ) -> Result<Option<Line<S>>, S::Error> {
+    self.stats.visited += 1;
+    if state.is_terminal() {
+      let utility = state.utility().into_iter().nth(player);
+      return Ok(utility.map(|utility| (vec![], utility.into())));
+    }
+    if depth < self.depth {
+      if let Some(outcomes) = state.outcomes() {
+        let outcomes = outcomes.into_iter().collect::<Vec<_>>();
+        if !outcomes.is_empty() {
+          let value = self.chance(state, outcomes, depth, player, (alpha, beta))?;
+          return Ok(value.map(|value| (vec![], value)));
+        }
+      } else {
+        let actions = state.actions().into_iter().collect::<Vec<_>>();
+        if !actions.is_empty() {
+          return self.choice(state, actions, depth, player, (alpha, beta));
+        }
+      }
+    }
+
+    let observed = state.observe()?;
+    Ok(Some((vec![], self.state_value.value(&observed).into())))
+  }
+
+  /// Finds the best action for the player to move.
+  fn choice(
+    &mut self,
+    state: &S,
+    actions: Vec<S::Action>,
+    depth: usize,
+    player: usize,
+    (mut alpha, mut beta): (f64, f64),
+  ) -> Result<Option<Line<S>>, S::Error> {
+    let maximising = state.player() == player;
+    let mut best: Option<Line<S>> = None;
+    for action in actions {
+      let child = state.result(&action)?;
+      let result = self.expectimax_recursive(&child, depth + 1, player, (alpha, beta))?;
+      let Some((mut path, value)) = result else {
+        continue;
+      };
+      let improved = best.as_ref().is_none_or(|(_, best)| match maximising {
+        true => value > *best,
+        false => value < *best,
+      });
+      if !improved {
+        continue;
+      }
+      path.push(action);
+      best = Some((path, value));
+
+      if self.bounds.is_none() {
+        continue;
+      }
+      if maximising {
+        alpha = alpha.max(value);
+      } else {
+        beta = beta.min(value);
+      }
+      if alpha >= beta {
+        self.stats.cutoffs += 1;
+        break;
+      }
+    }
+    Ok(best)
+  }
+
+  /// Finds the expected value of a chance node.
+  ///
+  /// With bounds, each outcome is searched with the window that would let the
+  /// expected value reach the parent's window, given the outcomes searched so
+  /// far and the bounds on those that are left (Star1). If the value of any
+  /// outcome can't be found, neither can the expected value.
+  fn chance(
+    &mut self,
+    state: &S,
+    outcomes: Vec<(S::Action, f64)>,
+    depth: usize,
+    player: usize,
+    (alpha, beta): (f64, f64),
+  ) -> Result<Option<f64>, S::Error> {
+    let (lower, upper) = self.bounds.unwrap_or((f64::NEG_INFINITY, f64::INFINITY));
+    let (mut expected, mut remaining) = (0.0, 1.0);
+    for (action, probability) in outcomes {
+      let child = state.result(&action)?;
+      remaining -= probability;
+      let window = match self.bounds {
+        Some(_) if probability > 0.0 => (
+          ((alpha - expected - remaining * upper) / probability).max(lower),
+          ((beta - expected - remaining * lower) / probability).min(upper),
+        ),
+        _ => (f64::NEG_INFINITY, f64::INFINITY),
+      };
+      let Some((_, value)) = self.expectimax_recursive(&child, depth + 1, player, window)? else {
+        return Ok(None);
+      };
+      expected += probability * value;
+
+      if self.bounds.is_none() {
+        continue;
+      }
+      if expected + remaining * upper <= alpha {
+        self.stats.cutoffs += 1;
+        return Ok(Some(expected + remaining * upper));
+      }
+      if expected + remaining * lower >= beta {
+        self.stats.cutoffs += 1;
+        return Ok(Some(expected + remaining * lower));
+      }
+    }
+    Ok(Some(expected))
+  }
+}
@@ -0,0 +1,211 @@
+//! The max^n algorithm, for games with any number of players.
+//!
+//! Each state is given a vector of values, one for each player, and every
+//! player chooses the action that maximises their own value. Alternatively,
+//! the paranoid assumption can be made, where every other player is assumed to
+//! be working against the player to move at the start.
+
+use super::GameStats;
+use crate::{
+  state::{Game, State},
+  timer::Timed,
+};
+use std::{marker::PhantomData, ops::Add};
+
+/// The actions chosen from a state, and the values of the state they lead to
+type Line<S> = (Vec<<S as State>::Action>, Vec<<S as Game>::Utility>);
+
+/// A max^n search, or a paranoid search if `paranoid` is set.
+///
+/// Values are compared using the component for the relevant player, so
+/// `state_value` should give the value of a state to each player, in the same
+/// form as `Game::utility`.
+///
+/// Under the paranoid assumption, the game is reduced to two players: the
+/// player to move at the start, and everyone else minimising its value. This
+/// allows alpha-beta pruning, so it can search deeper than max^n, at the cost
+/// of assuming the worst of the other players.
+///
+/// Max^n can only prune when the values are bounded. Given the largest sum of
+/// the values of a state, and that no value is below the default (i.e. 0),
+/// shallow pruning stops searching a state once the player to move has found a
+/// value that leaves too little for the player above to do any better.
+pub struct MaxN<S: Game, H> {
+  /// The value of a state to each player, used at the depth limit
+  pub state_value: H,
+  /// The number of actions to look ahead
+  pub depth: usize,
+  /// Whether to make the paranoid assumption rather than use max^n
+  pub paranoid: bool,
+  /// The largest sum of the values of any state, if known
+  pub max_sum: Option<S::Utility>,
+  stats: GameStats,
+  _state: PhantomData<S>,
+}
+
+impl<S: Game, H> MaxN<S, H> {
+  /// Creates a new max^n search, without pruning
+  pub fn new(state_value: H, depth: usize) -> Self {
+    Self {
+      state_value,
+      depth,
+      paranoid: false,
+      max_sum: None,
+      stats: GameStats::default(),
+      _state: PhantomData,
+    }
+  }
+
+  /// Creates a new search under the paranoid assumption
+  pub fn paranoid(state_value: H, depth: usize) -> Self {
+    Self {
+      paranoid: true,
+      ..Self::new(state_value, depth)
+    }
+  }
+
+  /// Enables shallow pruning, given the largest sum of the values of a state.
+  ///
+  /// Every value must be at least the default (i.e. 0) for this to be correct.
+  pub fn with_max_sum(mut self, max_sum: S::Utility) -> Self {
+    self.max_sum = Some(max_sum);
+    self
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &GameStats {
+    &self.stats
+  }
+}
+
+impl<S: Game, H> MaxN<S, H>
+where
+  S::Action: Clone,
+  S::Utility: Ord + Clone + Add<Output = S::Utility>,
+  H: Fn(&S::Observation) -> Vec<S::Utility>,
+{
+  /// Searches from the given state, returning the actions each player is
+  /// expected to choose and the values of the state they lead to.
+  ///
+  /// Values are vectors indexed by player, and any action leading to a vector
+  /// without a value for the player choosing it is skipped. Returns `None` if
+  /// every action from the start is skipped.
+  pub fn maxn(&mut self, start: S) -> Result<Option<Line<S>>, S::Error> {
+    let started = self.stats.start();
+    let result = match self.paranoid {
+      true => self.paranoid_recursive(&start, 0, start.player(), (None, None)),
+      false => self.maxn_recursive(&start, 0, None),
+    };
+    self.stats.finish(started);
+    Ok(result?.map(|(mut actions, values)| {
+      actions.reverse();
+      (actions, values)
+    }))
+  }
+
+  /// The values of a state that won't be searched any deeper, if it is a leaf.
+  fn leaf(&mut self, state: &S, depth: usize) -> Result<Option<Vec<S::Utility>>, S::Error> {
+    self.stats.visited += 1;
+    if state.is_terminal() {
+      return Ok(Some(state.utility()));
+    }
+    if depth < self.depth && state.actions().into_iter().next().is_some() {
+      return Ok(None);
+    }
+    Ok(Some((self.state_value)(&state.observe()?)))
+  }
+
+  /// Finds the values of a state, with the actions chosen from it in reverse.
+  ///
+  /// `bound` is the best value found so far by the player choosing this state,
+  /// used for shallow pruning.
+  fn maxn_recursive(
+    &mut self,
+    state: &S,
+    depth: usize,
+    bound: Option<&S::Utility>,
+  ) -> Result<Option<Line<S>>, S::Error> {
+    if let Some(values) = self.leaf(state, depth)? {
+      return Ok(Some((vec![], values)));
+    }
+
+    let player = state.player();
+    let mut best: Option<(Line<S>, S::Utility)> = None;
+    for action in state.actions() {
+      let child = state.result(&action)?;
+      // A player taking another turn chooses for themselves, so their best
+      // value so far is no bound on what they'll choose next
+      let child_bound = match child.player() == player {
+        true => None,
+        false => best.as_ref().map(|(_, value)| value),
+      };
+      let Some((mut path, values)) = self.maxn_recursive(&child, depth + 1, child_bound)? else {
+        continue;
+      };
+      let Some(value) = values.get(player).cloned() else {
+        continue;
+      };
+      if best.as_ref().is_some_and(|(_, best)| value <= *best) {
+        continue;
+      }
+      path.push(action);
+      best = Some(((path, values), value.clone()));
+
+      // The player above can get at most `max_sum - value` from this state
+      let max_sum = self.max_sum.as_ref();
+      if (max_sum.zip(bound)).is_some_and(|(max_sum, bound)| &(value + bound.clone()) >= max_sum) {
+        self.stats.cutoffs += 1;
+        break;
+      }
+    }
+    Ok(best.map(|(line, _)| line))
+  }
+
+  /// Finds the values of a state under the paranoid assumption, pruning with
+  /// the window `(alpha, beta)` on the value to the player `root`.
+  fn paranoid_recursive(
+    &mut self,
+    state: &S,
+    depth: usize,
+    root: usize,
+    (mut alpha, mut beta): (Option<S::Utility>, Option<S::Utility>),
+  ) -> Result<Option<Line<S>>, S::Error> {
+    if let Some(values) = self.leaf(state, depth)? {
+      return Ok(Some((vec![], values)));
+    }
+
+    let maximising = state.player() == root;
+    let mut best: Option<(Line<S>, S::Utility)> = None;
+    for action in state.actions() {
+      let child = state.result(&action)?;
+      let window = (alpha.clone(), beta.clone());
+      let Some((mut path, values)) = self.paranoid_recursive(&child, depth + 1, root, window)?
+      else {
+        continue;
+      };
+      let Some(value) = values.get(root).cloned() else {
+        continue;
+      };
+      let improved = best.as_ref().is_none_or(|(_, best)| match maximising {
+        true => value > *best,
+        false => value < *best,
+      });
+      if !improved {
+        continue;
+      }
+      path.push(action);
+      best = Some(((path, values), value.clone()));
+
+      if maximising {
+        alpha = Some(alpha.map_or(value.clone(), |alpha| alpha.max(value)));
+      } else {
+        beta = Some(beta.map_or(value.clone(), |beta| beta.min(value)));
+      }
+      if (alpha.as_ref().zip(beta.as_ref())).is_some_and(|(alpha, beta)| alpha >= beta) {
+        self.stats.cutoffs += 1;
+        break;
+      }
+    }
+    Ok(best.map(|(line, _)| line))
+  }
+}
@@ -0,0 +1,480 @@
+//! Monte Carlo tree search.
+//!
+//! This estimates the value of each action from the starting state by playing
+//! out games from it, focusing on the actions that look best so far. Unlike
+//! minimax, this needs no evaluation heuristic or fixed depth, just a way to
+//! score playouts that stop before the game is over.
+
+use super::GameStats;
+use crate::{
+  state::{Game, State},
+  timer::Timed,
+};
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use std::{marker::PhantomData, time::Duration};
+
+/// What is known about a node of the search tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NodeStats {
+  /// The number of playouts that have passed through the node
+  pub visits: usize,
+  /// The value of the node to the player who chose its action, as kept by the
+  /// `Backpropagation` policy
+  pub value: f64,
+}
+
+/// Chooses which child to descend into once every action has been tried.
+pub trait Selection {
+  /// Returns the index of the child to descend into, given the stats of the
+  /// node and of each of its children.
+  ///
+  /// The values of the children are all for the player choosing between them.
+  fn select(&mut self, node: &NodeStats, children: &[NodeStats]) -> usize;
+}
+
+/// Upper confidence bounds applied to trees, the usual selection policy.
+///
+/// Picks the child with the highest `value + exploration * sqrt(ln(N) / n)`,
+/// where `N` and `n` are the visits to the node and the child. This balances
+/// trying the best child so far against children with few visits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Uct {
+  /// How strongly to favour children with few visits
+  pub exploration: f64,
+}
+
+impl Default for Uct {
+  /// The usual exploration constant of `sqrt(2)`, for rewards between 0 and 1
+  fn default() -> Self {
+    Self {
+      exploration: std::f64::consts::SQRT_2,
+    }
+  }
+}
+
+impl Selection for Uct {
+  fn select(&mut self, node: &NodeStats, children: &[NodeStats]) -> usize {
+    let ln_visits = (node.visits.max(1) as f64).ln();
+    let bound = |child: &NodeStats| match child.visits {
+      0 => f64::INFINITY,
+      visits => child.value + self.exploration * (ln_visits / visits as f64).sqrt(),
+    };
+    let bounds = children.iter().map(bound).enumerate();
+    let best = bounds.max_by(|(_, lhs), (_, rhs)| lhs.total_cmp(rhs));
+    best.map_or(0, |(child, _)| child)
+  }
+}
+
+/// Chooses which untried action of a node to add to the tree next.
+pub trait Expansion<S: State> {
+  /// Returns the index of the action to try next, out of those untried.
+  fn expand(&mut self, state: &S, untried: &[S::Action], rng: &mut dyn RngCore) -> usize;
+}
+
+/// Tries the actions of each node in a random order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RandomExpansion;
+
+impl<S: State> Expansion<S> for RandomExpansion {
+  fn expand(&mut self, _: &S, untried: &[S::Action], rng: &mut dyn RngCore) -> usize {
+    rng.gen_range(0..untried.len())
+  }
+}
+
+/// Tries the actions of each node in the order they are given by the state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OrderedExpansion;
+
+impl<S: State> Expansion<S> for OrderedExpansion {
+  fn expand(&mut self, _: &S, _: &[S::Action], _: &mut dyn RngCore) -> usize {
+    0
+  }
+}
+
+/// Plays out a game from a newly added node to estimate its value.
+///
+/// This is only used for nodes that aren't terminal, as the utility of a
+/// terminal node is its reward.
+pub trait Rollout<S: State> {
+  /// Plays out the game from the given state, returning the reward of each
+  /// player (indexed as by `Game::player`).
+  fn rollout(&mut self, state: S, rng: &mut dyn RngCore) -> Result<Vec<f64>, S::Error>;
+}
+
+/// Plays out games by taking random actions, until a terminal state or an
+/// optional depth is reached.
+///
+/// A playout that reaches a terminal state is scored by its utility. Otherwise
+/// it's scored by `reward`, which is given the observation of the last state
+/// and returns the reward of each player.
+#[derive(Clone, Debug)]
+pub struct RandomRollout<R> {
+  /// Scores the end of a playout for each player
+  pub reward: R,
+  /// The maximum number of actions to take in a playout
+  pub max_depth: Option<usize>,
+}
+
+impl<R> RandomRollout<R> {
+  /// Creates a new rollout policy that plays until no actions remain
+  pub fn new(reward: R) -> Self {
+    Self {
+      reward,
+      max_depth: None,
+    }
+  }
+
+  /// Stops playouts after the given number of actions
+  pub fn max_depth(mut self, max_depth: usize) -> Self {
+    self.max_depth = Some(max_depth);
+    self
+  }
+}
+
+impl<S: Game, R> Rollout<S> for RandomRollout<R>
+where
+  S::Utility: Into<f64>,
+  R: Fn(&S::Observation) -> Vec<f64>,
+{
+  fn rollout(&mut self, mut state: S, rng: &mut dyn RngCore) -> Result<Vec<f64>, S::Error> {
+    let mut depth = 0;
+    loop {
+      if state.is_terminal() {
+        return Ok(rewards(&state));
+      }
+      let mut actions = state.actions().into_iter().collect::<Vec<_>>();
+      if actions.is_empty() || self.max_depth.is_some_and(|max| depth >= max) {
+        return Ok((self.reward)(&state.observe()?));
+      }
+      let action = actions.swap_remove(rng.gen_range(0..actions.len()));
+      state = state.result(&action)?;
+      depth += 1;
+    }
+  }
+}
+
+/// The utility of a terminal state to each player, as rewards
+fn rewards<S: Game>(state: &S) -> Vec<f64>
+where
+  S::Utility: Into<f64>,
+{
+  state.utility().into_iter().map(Into::into).collect()
+}
+
+/// Updates the nodes along the path of a playout with its reward.
+pub trait Backpropagation {
+  /// Updates a node with the reward of the player who chose its action.
+  ///
+  /// The visits of the node already include this playout.
+  fn backup(&mut self, node: &mut NodeStats, reward: f64);
+}
+
+/// Keeps the mean reward of each node, the usual backpropagation policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Average;
+
+impl Backpropagation for Average {
+  fn backup(&mut self, node: &mut NodeStats, reward: f64) {
+    node.value += (reward - node.value) / node.visits as f64;
+  }
+}
+
+/// Keeps the best reward of each node.
+///
+/// This suits single player puzzles, where a good action only needs one good
+/// playout through it, rather than doing well on average.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BestReward;
+
+impl Backpropagation for BestReward {
+  fn backup(&mut self, node: &mut NodeStats, reward: f64) {
+    if node.visits == 1 || reward > node.value {
+      node.value = reward;
+    }
+  }
+}
+
+/// What was learnt about one of the actions from the starting state.
+#[derive(Clone, Debug)]
+pub struct ActionStats<A> {
+  /// The action taken from the starting state
+  pub action: A,
+  /// The number of playouts that started with the action
+  pub visits: usize,
+  /// The value of the action to the player taking it
+  pub value: f64,
+}
+
+/// The result of a Monte Carlo tree search.
+#[derive(Clone, Debug)]
+pub struct MctsResult<A> {
+  /// The stats of each action from the starting state, in the order they were
+  /// first tried
+  pub actions: Vec<ActionStats<A>>,
+  /// The number of playouts run
+  pub iterations: usize,
+}
+
+impl<A> MctsResult<A> {
+  /// The action with the most visits, which is the most reliable choice as the
+  /// search has spent the most effort confirming its value.
+  pub fn best(&self) -> Option<&ActionStats<A>> {
+    self.actions.iter().max_by_key(|stats| stats.visits)
+  }
+}
+
+/// A node of the search tree, with the state reached by its action.
+struct Node<S: State> {
+  state: S,
+  action: Option<S::Action>,
+  parent: Option<usize>,
+  children: Vec<usize>,
+  untried: Option<Vec<S::Action>>,
+  stats: NodeStats,
+}
+
+/// A Monte Carlo tree search, using the UCT selection policy by default.
+///
+/// Each iteration selects a path down the tree, adds a node for an untried
+/// action, plays out the game from it and then updates the nodes along the path
+/// with the reward. Each of these steps is done by a pluggable policy.
+///
+/// The search runs for a fixed number of iterations, or until its time budget
+/// runs out, whichever comes first.
+///
+/// Rewards are indexed by player, as given by `Game::player`, so the value of
+/// each node is the reward of the player to move in its parent. Terminal nodes
+/// are never expanded, and are rewarded with their utility.
+///
+/// A playout with no reward for a player is skipped by the nodes they chose,
+/// rather than scored as 0, and a node that has never had a reward is dropped
+/// from the tree, as with a missing utility in `Expectimax`.
+pub struct Mcts<S, R, P = Uct, E = RandomExpansion, B = Average> {
+  /// The maximum number of playouts to run
+  pub iterations: usize,
+  /// The maximum time to spend searching
+  pub time_budget: Option<Duration>,
+  rollout: R,
+  selection: P,
+  expansion: E,
+  backpropagation: B,
+  rng: StdRng,
+  stats: GameStats,
+  _state: PhantomData<S>,
+}
+
+impl<S, R> Mcts<S, R> {
+  /// Creates a new search that runs the given number of playouts
+  pub fn new(rollout: R, iterations: usize) -> Self {
+    Self {
+      iterations,
+      time_budget: None,
+      rollout,
+      selection: Uct::default(),
+      expansion: RandomExpansion,
+      backpropagation: Average,
+      rng: StdRng::from_entropy(),
+      stats: GameStats::default(),
+      _state: PhantomData,
+    }
+  }
+}
+
+impl<S, R, P, E, B> Mcts<S, R, P, E, B> {
+  /// Stops each search once the given time has passed
+  pub fn time_budget(mut self, time_budget: Duration) -> Self {
+    self.time_budget = Some(time_budget);
+    self
+  }
+
+  /// Seeds the random number generator, so searches can be repeated
+  pub fn seed(mut self, seed: u64) -> Self {
+    self.rng = StdRng::seed_from_u64(seed);
+    self
+  }
+
+  /// Sets the policy for choosing which child to descend into
+  pub fn selection<P2: Selection>(self, selection: P2) -> Mcts<S, R, P2, E, B> {
+    Mcts {
+      iterations: self.iterations,
+      time_budget: self.time_budget,
+      rollout: self.rollout,
+      selection,
+      expansion: self.expansion,
+      backpropagation: self.backpropagation,
+      rng: self.rng,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+
+  /// Sets the policy for choosing which untried action to add to the tree
+  pub fn expansion<E2>(self, expansion: E2) -> Mcts<S, R, P, E2, B> {
+    Mcts {
+      iterations: self.iterations,
+      time_budget: self.time_budget,
+      rollout: self.rollout,
+      selection: self.selection,
+      expansion,
+      backpropagation: self.backpropagation,
+      rng: self.rng,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+
+  /// Sets the policy for updating the tree with the reward of a playout
+  pub fn backpropagation<B2: Backpropagation>(self, backpropagation: B2) -> Mcts<S, R, P, E, B2> {
+    Mcts {
+      iterations: self.iterations,
+      time_budget: self.time_budget,
+      rollout: self.rollout,
+      selection: self.selection,
+      expansion: self.expansion,
+      backpropagation,
+      rng: self.rng,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &GameStats {
+    &self.stats
+  }
+}
+
+impl<S: Game, R, P, E, B> Mcts<S, R, P, E, B>
+where
+  S: Clone,
+  S::Action: Clone,
+  S::Utility: Into<f64>,
+  R: Rollout<S>,
+  P: Selection,
+  E: Expansion<S>,
+  B: Backpropagation,
+{
+  /// Searches from the given state, returning the stats of each action from it.
+  ///
+  /// Fails if any state reached can't be expanded or observed.
+  pub fn search(&mut self, start: S) -> Result<MctsResult<S::Action>, S::Error> {
+    let started = self.stats.start();
+    let mut tree = vec![Node {
+      state: start,
+      action: None,
+      parent: None,
+      children: vec![],
+      untried: None,
+      stats: NodeStats::default(),
+    }];
+
+    let mut iterations = 0;
+    while iterations < self.iterations
+      && (self.time_budget).is_none_or(|budget| started.elapsed() < budget)
+    {
+      let leaf = self.select(&mut tree);
+      let leaf = self.expand(&mut tree, leaf)?;
+      let state = &tree[leaf].state;
+      let rewards = match state.is_terminal() {
+        true => rewards(state),
+        false => self.rollout.rollout(state.clone(), &mut self.rng)?,
+      };
+      self.backup(&mut tree, leaf, &rewards);
+      iterations += 1;
+    }
+
+    let root = &tree[0];
+    let actions = root.children.iter().map(|&child| {
+      let child = &tree[child];
+      ActionStats {
+        action: child
+          .action
+          .clone()
+          .expect("children should have an action"),
+        visits: child.stats.visits,
+        value: child.stats.value,
+      }
+    });
+    let result = MctsResult {
+      actions: actions.collect(),
+      iterations,
+    };
+    self.stats.finish(started);
+    Ok(result)
+  }
+
+  /// Descends from the root until reaching a node with untried actions, or a
+  /// node with no children, such as a terminal node.
+  fn select(&mut self, tree: &mut [Node<S>]) -> usize {
+    let mut id = 0;
+    loop {
+      self.stats.visited += 1;
+      let node = &mut tree[id];
+      let state = &node.state;
+      let untried = (node.untried).get_or_insert_with(|| match state.is_terminal() {
+        true => vec![],
+        false => state.actions().into_iter().collect(),
+      });
+      if !untried.is_empty() || node.children.is_empty() {
+        return id;
+      }
+      let node = &tree[id];
+      let children = node.children.iter().map(|&child| tree[child].stats);
+      let child = (self.selection).select(&node.stats, &children.collect::<Vec<_>>());
+      id = node.children[child];
+    }
+  }
+
+  /// Adds a child for one of the untried actions of a node, returning the
+  /// child, or the node itself if it has no untried actions.
+  fn expand(&mut self, tree: &mut Vec<Node<S>>, id: usize) -> Result<usize, S::Error> {
+    let node = &mut tree[id];
+    let Some(untried) = node.untried.as_mut().filter(|untried| !untried.is_empty()) else {
+      return Ok(id);
+    };
+    let action = untried.remove(self.expansion.expand(&node.state, untried, &mut self.rng));
+    let state = node.state.result(&action)?;
+
+    let child = tree.len();
+    tree[id].children.push(child);
+    tree.push(Node {
+      state,
+      action: Some(action),
+      parent: Some(id),
+      children: vec![],
+      untried: None,
+      stats: NodeStats::default(),
+    });
+    self.stats.visited += 1;
+    Ok(child)
+  }
+
+  /// Updates each node from the given one up to the root, with the reward of
+  /// the player who chose its action, skipping nodes with no reward for them.
+  fn backup(&mut self, tree: &mut [Node<S>], mut id: usize, rewards: &[f64]) {
+    loop {
+      // The root has no action, so its value is for the player to move
+      let mover = tree[id].parent.unwrap_or(id);
+      let player = tree[mover].state.player();
+      let node = &mut tree[id];
+      let parent = node.parent;
+      match rewards.get(player) {
+        Some(&reward) => {
+          node.stats.visits += 1;
+          self.backpropagation.backup(&mut node.stats, reward);
+        }
+        // Otherwise it would always be selected, as it has no visits
+        None if node.stats.visits == 0 => {
+          if let Some(parent) = parent {
+            tree[parent].children.retain(|&child| child != id);
+          }
+        }
+        None => {}
+      }
+      let Some(parent) = parent else {
+        return;
+      };
+      id = parent;
+    }
+  }
+}
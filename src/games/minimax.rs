@@ -2,59 +2,208 @@
 //!
 //! This attempts to find the best actions to take from the starting state,
 //! assuming both players play optimally.
+//!
+//! The player to move at the start maximises the value of states, and every
+//! other player minimises it. With more than two players, this is the paranoid
+//! assumption, so use `MaxN` for players that pursue their own interests.
+//!
+//! States at the depth limit are valued by the heuristic, unless a quiescence
+//! search is used, which keeps searching noisy actions (see `Game::is_noisy`)
+//! until the state is quiet. This avoids the horizon effect, where the value
+//! of a state is thrown off by an action just beyond the depth limit.
 
-use crate::{prelude::SearchHeuristic, state::State};
+use super::{Entry, GameStats, TranspositionTable, Transpositions, ValueBound};
+use crate::{
+  prelude::SearchHeuristic,
+  state::{Game, State},
+  timer::Timed,
+};
 use std::marker::PhantomData;
 
-pub struct MiniMax<S, H> {
+/// The actions chosen from a state, and the value of the state they lead to
+type Line<S, C> = (Vec<<S as State>::Action>, C);
+
+pub struct MiniMax<S, H, T = ()> {
   pub state_value: H,
   pub depth: usize,
-  pub players: usize,
+  /// The number of noisy actions to search past the depth limit, if a
+  /// quiescence search is used
+  pub quiescence: Option<usize>,
+  table: T,
+  stats: GameStats,
   _state: PhantomData<S>,
 }
 
 impl<S, H> MiniMax<S, H> {
-  pub fn new(state_value: H, depth: usize, players: usize) -> Self {
+  pub fn new(state_value: H, depth: usize) -> Self {
     Self {
       state_value,
       depth,
-      players,
+      quiescence: None,
+      table: (),
+      stats: GameStats::default(),
+      _state: PhantomData,
+    }
+  }
+}
+
+impl<S: State, H: SearchHeuristic<S>> MiniMax<S, H> {
+  /// Stores the values of states in a transposition table, so that a state
+  /// reached again is only searched again if it needs to be searched deeper.
+  ///
+  /// The actions returned stop at the first state found in the table.
+  pub fn with_table(
+    self,
+    table: TranspositionTable<S::Action, H::Cost>,
+  ) -> MiniMax<S, H, TranspositionTable<S::Action, H::Cost>> {
+    MiniMax {
+      state_value: self.state_value,
+      depth: self.depth,
+      quiescence: self.quiescence,
+      table,
+      stats: self.stats,
       _state: PhantomData,
     }
   }
 }
 
-impl<S: State, H: SearchHeuristic<S>> MiniMax<S, H>
+impl<S, H, T> MiniMax<S, H, T> {
+  /// Enables a quiescence search, searching up to the given number of noisy
+  /// actions past the depth limit.
+  pub fn with_quiescence(mut self, depth: usize) -> Self {
+    self.quiescence = Some(depth);
+    self
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &GameStats {
+    &self.stats
+  }
+
+  /// The transposition table, if one is used
+  pub fn table(&self) -> &T {
+    &self.table
+  }
+}
+
+impl<S: Game<Utility = H::Cost>, H: SearchHeuristic<S>, T> MiniMax<S, H, T>
 where
   S::Action: Clone,
+  T: Transpositions<S, H::Cost>,
 {
-  pub fn minimax(&self, start: S) -> Option<(Vec<S::Action>, H::Cost)> {
-    self.minimax_recursive(start, vec![])
+  /// Searches from the given state, returning the actions expected to be
+  /// taken and the value of the state they lead to, or `None` if no value
+  /// could be found (such as when a utility is missing for the player).
+  ///
+  /// Fails if the result of an action or an observation of a state fails.
+  pub fn minimax(&mut self, start: S) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    let started = self.stats.start();
+    let player = start.player();
+    self.table.start(player);
+    let result = self.minimax_recursive(start, vec![], player);
+    self.stats.finish(started);
+    result
   }
 
   fn minimax_recursive(
-    &self,
+    &mut self,
     state: S,
     actions: Vec<S::Action>,
-  ) -> Option<(Vec<S::Action>, H::Cost)> {
-    let turn = actions.len();
-    if turn == self.depth {
-      let observed = state.observe().ok()?;
-      let value = self.state_value.value(&observed);
-      return Some((actions, value));
+    player: usize,
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    self.stats.visited += 1;
+    if state.is_terminal() {
+      let value = state.utility().into_iter().nth(player);
+      return Ok(value.map(|value| (actions, value)));
+    }
+    if actions.len() == self.depth {
+      return self.quiesce(&state, actions, player, self.quiescence.unwrap_or(0));
+    }
+    let remaining = self.depth - actions.len();
+    if let Some(entry) = self.table.probe(&state) {
+      if entry.depth >= remaining && entry.bound == ValueBound::Exact {
+        self.stats.transpositions += 1;
+        let mut actions = actions;
+        actions.extend(entry.action.clone());
+        return Ok(Some((actions, entry.value.clone())));
+      }
+    }
+    let maximising = state.player() == player;
+
+    let ply = actions.len();
+    let mut best = None;
+    for action in state.actions() {
+      let child = state.result(&action)?;
+      let mut actions = actions.clone();
+      actions.push(action);
+      let path = self.minimax_recursive(child, actions, player)?;
+      best = Self::better(best, path, maximising);
+    }
+    if let Some((path, value)) = &best {
+      let entry = Entry {
+        depth: remaining,
+        value: value.clone(),
+        bound: ValueBound::Exact,
+        action: path.get(ply).cloned(),
+      };
+      self.table.store(&state, entry);
+    }
+    Ok(best)
+  }
+
+  /// Finds the value of a state at the depth limit, searching up to `depth`
+  /// more noisy actions.
+  ///
+  /// The player to move may stand pat rather than take a noisy action, so the
+  /// value is never worse for them than that of the state itself.
+  fn quiesce(
+    &mut self,
+    state: &S,
+    actions: Vec<S::Action>,
+    player: usize,
+    depth: usize,
+  ) -> Result<Option<Line<S, H::Cost>>, S::Error> {
+    let observed = state.observe()?;
+    let stand_pat = (actions.clone(), self.state_value.value(&observed));
+    if depth == 0 {
+      return Ok(Some(stand_pat));
     }
+    let maximising = state.player() == player;
 
-    let paths = state.actions().into_iter().filter_map(|action| {
-      let state = state.result(&action).ok()?;
+    let mut best = Some(stand_pat);
+    for action in state.actions() {
+      if !state.is_noisy(&action) {
+        continue;
+      }
+      let child = state.result(&action)?;
       let mut actions = actions.clone();
       actions.push(action);
-      self.minimax_recursive(state, actions)
-    });
+      self.stats.visited += 1;
+      let path = if child.is_terminal() {
+        let value = child.utility().into_iter().nth(player);
+        value.map(|value| (actions, value))
+      } else {
+        self.quiesce(&child, actions, player, depth - 1)?
+      };
+      best = Self::better(best, path, maximising);
+    }
+    Ok(best)
+  }
 
-    if turn % self.players == 0 {
-      paths.max_by_key(|(_, value)| value.clone())
-    } else {
-      paths.min_by_key(|(_, value)| value.clone())
+  /// The better of two paths for the player choosing between them, keeping
+  /// the last of equally good paths when maximising and the first otherwise.
+  fn better(
+    best: Option<Line<S, H::Cost>>,
+    path: Option<Line<S, H::Cost>>,
+    maximising: bool,
+  ) -> Option<Line<S, H::Cost>> {
+    match (best, path) {
+      (Some(best), Some(path)) => match maximising {
+        true if path.1 >= best.1 => Some(path),
+        false if path.1 < best.1 => Some(path),
+        _ => Some(best),
+      },
+      (best, path) => best.or(path),
     }
   }
 }
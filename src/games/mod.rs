@@ -1,8 +1,23 @@
 // @done Implement MVP 2-Player minimax.<br>
 // @done Modify for N-Player minimax.<br>
-// @todo Add Alpha-Beta pruning.<br>
+// @done Add Alpha-Beta pruning.<br>
 
 mod alphabeta;
 pub use alphabeta::AlphaBeta;
+mod expectimax;
+pub use expectimax::Expectimax;
+mod maxn;
+pub use maxn::MaxN;
+mod mcts;
+pub use mcts::{
+  ActionStats, Average, Backpropagation, BestReward, Expansion, Mcts, MctsResult, NodeStats,
+  OrderedExpansion, RandomExpansion, RandomRollout, Rollout, Selection, Uct,
+};
 mod minimax;
 pub use minimax::MiniMax;
+mod ordering;
+pub use ordering::{History, Killers, MoveOrdering};
+mod stats;
+pub use stats::GameStats;
+mod transposition;
+pub use transposition::{Entry, TranspositionTable, Transpositions, ValueBound, ZobristKeys};
@@ -0,0 +1,186 @@
+//! Move ordering, for trying the actions most likely to be best first.
+//!
+//! Alpha-beta prunes the most when the best action from each state is searched
+//! first, so the order actions are tried in matters as much as how many there
+//! are.
+
+use crate::state::Game;
+use std::{cmp::Reverse, collections::HashMap, hash::Hash};
+
+/// A way of ordering the actions of a state before they are searched.
+///
+/// This is implemented for:
+///
+/// - `()`, which leaves actions in the order the state gives them
+/// - Functions that sort the actions of a state
+/// - `Killers`, for the killer move heuristic
+/// - `History`, for the history heuristic
+/// - Tuples of orderings, which are applied in turn, so that later orderings
+///   take priority and earlier orderings break ties
+pub trait MoveOrdering<S: Game> {
+  /// Sorts the actions of a state, with the actions to try first at the start.
+  /// `ply` is the number of actions taken since the start of the search.
+  fn order(&mut self, state: &S, ply: usize, actions: &mut [S::Action]);
+
+  /// Records that an action caused a cutoff, with `depth` actions left to
+  /// search after the state.
+  fn cutoff(&mut self, state: &S, ply: usize, depth: usize, action: &S::Action) {
+    let _ = (state, ply, depth, action);
+  }
+}
+
+impl<S: Game> MoveOrdering<S> for () {
+  fn order(&mut self, _state: &S, _ply: usize, _actions: &mut [S::Action]) {}
+}
+
+impl<S: Game, F: FnMut(&S, &mut [S::Action])> MoveOrdering<S> for F {
+  fn order(&mut self, state: &S, _ply: usize, actions: &mut [S::Action]) {
+    self(state, actions)
+  }
+}
+
+macro_rules! impl_move_ordering {
+  ($($t:ident),+) => {
+    impl<S: Game, $($t: MoveOrdering<S>),+> MoveOrdering<S> for ($($t),+) {
+      #[allow(non_snake_case)]
+      fn order(&mut self, state: &S, ply: usize, actions: &mut [S::Action]) {
+        let ($($t),+) = self;
+        $($t.order(state, ply, actions);)+
+      }
+
+      #[allow(non_snake_case)]
+      fn cutoff(&mut self, state: &S, ply: usize, depth: usize, action: &S::Action) {
+        let ($($t),+) = self;
+        $($t.cutoff(state, ply, depth, action);)+
+      }
+    }
+  };
+}
+
+impl_move_ordering!(A, B);
+impl_move_ordering!(A, B, C);
+impl_move_ordering!(A, B, C, D);
+
+/// Moves an action to the start of the actions, keeping the order of the rest,
+/// returning whether it was found.
+pub(crate) fn to_front<A: PartialEq>(actions: &mut [A], action: &A) -> bool {
+  match actions.iter().position(|a| a == action) {
+    Some(index) => {
+      actions[..=index].rotate_right(1);
+      true
+    }
+    None => false,
+  }
+}
+
+/// The killer move heuristic.
+///
+/// An action that caused a cutoff is likely to cause one from other states
+/// with the same number of actions taken, so the last two actions to cause a
+/// cutoff at each ply are tried first.
+#[derive(Clone, Debug)]
+pub struct Killers<A> {
+  killers: Vec<[Option<A>; 2]>,
+}
+
+impl<A> Killers<A> {
+  /// Creates the heuristic, with no killer moves
+  pub fn new() -> Self {
+    Self { killers: vec![] }
+  }
+
+  /// The killer moves at a ply, most recent first
+  pub fn at(&self, ply: usize) -> impl Iterator<Item = &A> {
+    self.killers.get(ply).into_iter().flatten().flatten()
+  }
+}
+
+impl<A> Default for Killers<A> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<S: Game> MoveOrdering<S> for Killers<S::Action>
+where
+  S::Action: Clone + PartialEq,
+{
+  fn order(&mut self, _state: &S, ply: usize, actions: &mut [S::Action]) {
+    let Some(killers) = self.killers.get(ply) else {
+      return;
+    };
+    for killer in killers.iter().rev().flatten() {
+      to_front(actions, killer);
+    }
+  }
+
+  fn cutoff(&mut self, _state: &S, ply: usize, _depth: usize, action: &S::Action) {
+    if self.killers.len() <= ply {
+      self.killers.resize_with(ply + 1, Default::default);
+    }
+    let killers = &mut self.killers[ply];
+    if killers[0].as_ref() != Some(action) {
+      killers[1] = killers[0].replace(action.clone());
+    }
+  }
+}
+
+/// The history heuristic.
+///
+/// Each player's actions are scored by how often they have caused a cutoff,
+/// weighted towards cutoffs with more of the search left below them, and tried
+/// in order of their score.
+#[derive(Clone, Debug)]
+pub struct History<A> {
+  scores: Vec<HashMap<A, usize>>,
+}
+
+impl<A> History<A> {
+  /// Creates the heuristic, with every action scored 0
+  pub fn new() -> Self {
+    Self { scores: vec![] }
+  }
+
+  /// Halves every score, so that recent cutoffs count for more
+  pub fn age(&mut self) {
+    for scores in &mut self.scores {
+      scores.values_mut().for_each(|score| *score /= 2);
+      scores.retain(|_, score| *score > 0);
+    }
+  }
+}
+
+impl<A: Hash + Eq> History<A> {
+  /// The score of an action for a player
+  pub fn score(&self, player: usize, action: &A) -> usize {
+    let score = self
+      .scores
+      .get(player)
+      .and_then(|scores| scores.get(action));
+    score.copied().unwrap_or(0)
+  }
+}
+
+impl<A> Default for History<A> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<S: Game> MoveOrdering<S> for History<S::Action>
+where
+  S::Action: Clone + Hash + Eq,
+{
+  fn order(&mut self, state: &S, _ply: usize, actions: &mut [S::Action]) {
+    let player = state.player();
+    actions.sort_by_key(|action| Reverse(self.score(player, action)));
+  }
+
+  fn cutoff(&mut self, state: &S, _ply: usize, depth: usize, action: &S::Action) {
+    let player = state.player();
+    if self.scores.len() <= player {
+      self.scores.resize_with(player + 1, HashMap::new);
+    }
+    *self.scores[player].entry(action.clone()).or_default() += depth * depth;
+  }
+}
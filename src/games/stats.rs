@@ -0,0 +1,26 @@
+use crate::timer::Timed;
+use std::time::Duration;
+
+/// Statistics on the work done by the last call to a game search.
+#[derive(Clone, Debug, Default)]
+pub struct GameStats {
+  /// The number of game states visited, including leaves
+  pub visited: usize,
+  /// The number of times the remaining actions of a state were skipped, as
+  /// they could not change the result
+  pub cutoffs: usize,
+  /// The number of game states whose value was taken from a transposition
+  /// table rather than searched
+  pub transpositions: usize,
+  /// The depth of the deepest search that finished, for searches that deepen
+  /// iteratively
+  pub depth: usize,
+  /// The time taken by the search
+  pub elapsed: Duration,
+}
+
+impl Timed for GameStats {
+  fn elapsed_mut(&mut self) -> &mut Duration {
+    &mut self.elapsed
+  }
+}
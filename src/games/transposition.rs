@@ -0,0 +1,187 @@
+//! Transposition tables, for reusing the values of states that are reached by
+//! more than one sequence of actions.
+//!
+//! States are recognised by their Zobrist hash, where every feature of a state
+//! (such as a piece on a square) is given a random key, and the hash of the
+//! state is the xor of the keys of its features. Taking an action then only
+//! needs to xor out the features removed and xor in those added.
+
+use crate::state::{State, Zobrist};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Random keys for each feature of a state, for computing Zobrist hashes.
+#[derive(Clone, Debug)]
+pub struct ZobristKeys {
+  keys: Vec<u64>,
+}
+
+impl ZobristKeys {
+  /// Generates keys for the given number of features, from the given seed
+  pub fn new(features: usize, seed: u64) -> Self {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let keys = (0..features).map(|_| rng.gen()).collect();
+    Self { keys }
+  }
+
+  /// The key for a feature, to be xored into the hash when it is added to or
+  /// removed from a state
+  pub fn key(&self, feature: usize) -> u64 {
+    self.keys[feature]
+  }
+
+  /// The hash of a state with the given features, computed from scratch
+  pub fn hash(&self, features: impl IntoIterator<Item = usize>) -> u64 {
+    features
+      .into_iter()
+      .fold(0, |hash, feature| hash ^ self.key(feature))
+  }
+}
+
+/// What the value stored for a state is known to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueBound {
+  /// The value is exact
+  Exact,
+  /// The true value is at least the value, as the search was cut off
+  Lower,
+  /// The true value is at most the value, as no action reached the window
+  Upper,
+}
+
+/// The result of searching a state, as stored in a transposition table.
+#[derive(Clone, Debug)]
+pub struct Entry<A, V> {
+  /// The number of actions searched beyond the state
+  pub depth: usize,
+  /// The value of the state to the player to move at the start of the search
+  pub value: V,
+  /// What the value is known to be
+  pub bound: ValueBound,
+  /// The best action found from the state, if any
+  pub action: Option<A>,
+}
+
+/// A slot in the table, with the hash of the state it holds and the search
+/// that stored it
+type Slot<A, V> = Option<(u64, usize, Entry<A, V>)>;
+
+/// A transposition table holding a bounded number of entries.
+///
+/// Each hash maps to a single slot, so entries can replace each other. An
+/// entry is kept over a new one for a different state only if it was stored
+/// by the current search and searched deeper.
+#[derive(Clone, Debug)]
+pub struct TranspositionTable<A, V> {
+  slots: Vec<Slot<A, V>>,
+  player: Option<usize>,
+  generation: usize,
+}
+
+impl<A, V> TranspositionTable<A, V> {
+  /// Creates a table with room for the given number of entries
+  pub fn new(capacity: usize) -> Self {
+    assert!(
+      capacity > 0,
+      "a transposition table needs at least one slot"
+    );
+    Self {
+      slots: (0..capacity).map(|_| None).collect(),
+      player: None,
+      generation: 0,
+    }
+  }
+
+  /// The number of entries the table can hold
+  pub fn capacity(&self) -> usize {
+    self.slots.len()
+  }
+
+  /// The number of entries in the table
+  pub fn len(&self) -> usize {
+    self.slots.iter().filter(|slot| slot.is_some()).count()
+  }
+
+  /// Whether the table has no entries
+  pub fn is_empty(&self) -> bool {
+    self.slots.iter().all(Option::is_none)
+  }
+
+  /// Removes every entry
+  pub fn clear(&mut self) {
+    self.slots.iter_mut().for_each(|slot| *slot = None);
+  }
+
+  /// Returns the entry for the state with the given hash, if there is one
+  pub fn get(&self, hash: u64) -> Option<&Entry<A, V>> {
+    match &self.slots[self.index(hash)] {
+      Some((stored, _, entry)) if *stored == hash => Some(entry),
+      _ => None,
+    }
+  }
+
+  /// Stores the entry for the state with the given hash, unless it would
+  /// replace a deeper entry for another state from the current search
+  pub fn insert(&mut self, hash: u64, entry: Entry<A, V>) {
+    let (index, generation) = (self.index(hash), self.generation);
+    let slot = &mut self.slots[index];
+    let replace = match slot {
+      Some((stored, stored_generation, stored_entry)) => {
+        *stored == hash || *stored_generation != generation || stored_entry.depth <= entry.depth
+      }
+      None => true,
+    };
+    if replace {
+      *slot = Some((hash, generation, entry));
+    }
+  }
+
+  fn index(&self, hash: u64) -> usize {
+    (hash % self.slots.len() as u64) as usize
+  }
+}
+
+/// Somewhere to store the results of searching states, so they can be reused
+/// when a state is reached again.
+///
+/// This is implemented by `TranspositionTable` for states with a Zobrist hash,
+/// and by `()` for searches that don't store anything.
+pub trait Transpositions<S: State, V> {
+  /// Prepares for a new search, where values are for the given player
+  fn start(&mut self, player: usize);
+
+  /// Returns the entry for a state, if there is one
+  fn probe(&self, state: &S) -> Option<&Entry<S::Action, V>>;
+
+  /// Stores the entry for a state
+  fn store(&mut self, state: &S, entry: Entry<S::Action, V>);
+}
+
+impl<S: State, V> Transpositions<S, V> for () {
+  fn start(&mut self, _player: usize) {}
+
+  fn probe(&self, _state: &S) -> Option<&Entry<S::Action, V>> {
+    None
+  }
+
+  fn store(&mut self, _state: &S, _entry: Entry<S::Action, V>) {}
+}
+
+impl<S: Zobrist, V> Transpositions<S, V> for TranspositionTable<S::Action, V> {
+  /// Older entries may be replaced, and every entry is removed if the values
+  /// were for another player.
+  fn start(&mut self, player: usize) {
+    if self.player != Some(player) {
+      self.clear();
+      self.player = Some(player);
+    }
+    self.generation += 1;
+  }
+
+  fn probe(&self, state: &S) -> Option<&Entry<S::Action, V>> {
+    self.get(state.zobrist())
+  }
+
+  fn store(&mut self, state: &S, entry: Entry<S::Action, V>) {
+    self.insert(state.zobrist(), entry)
+  }
+}
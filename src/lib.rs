@@ -12,22 +12,53 @@
 //! - [X] Breadth First Search
 //! - [X] Depth First Search
 //! - [X] Iterative Deepening Search
+//! - [X] Bidirectional Breadth First Search
 //!
 //! ### Informed Search
 //!
 //! - [X] Uniform Cost Search
 //! - [X] Greedy Best First Search
+//! - [X] Beam Search
 //! - [X] A* Search
+//! - [X] Weighted A* Search
+//! - [X] Anytime Repairing A* Search
+//! - [X] Iterative Deepening A* Search
+//! - [X] Recursive Best First Search
+//! - [X] Simplified Memory Bounded A* Search
+//! - [X] Bidirectional A* Search
+//! - [X] Hash Distributed A* Search (Parallel)
+//! - [X] Portfolio of Searches (Parallel)
 //!
 //! ### Games
 //!
-//! - [ ] Minimax Search
-//! - [ ] Alpha Beta Pruning
+//! - [X] Minimax Search
+//! - [X] Alpha Beta Pruning
+//! - [X] Iterative Deepening (with Time Management)
+//! - [X] Move Ordering (Killer Moves, History Heuristic)
+//! - [X] Principal Variation Search (with Aspiration Windows)
+//! - [X] Null-Move Pruning and Late Move Reductions
+//! - [X] Quiescence Search
+//! - [X] Transposition Tables (with Zobrist Hashing)
+//! - [X] Expectimax Search
+//! - [X] Star1 (*-Minimax) Pruning
+//! - [X] Max^n Search (with Shallow Pruning)
+//! - [X] Paranoid Search
+//! - [X] Monte Carlo Tree Search
+//!
+//! ### Local Search
+//!
+//! - [X] Hill Climbing (Steepest Ascent and First Choice, with Random Restarts)
+//! - [X] Simulated Annealing
+//! - [X] Tabu Search
+//! - [X] Genetic Algorithm
 
 pub mod games;
+pub mod local;
 pub mod prelude;
 pub mod search;
 pub mod state;
 pub mod traits;
 pub mod value;
 pub mod wrappers;
+
+mod timer;
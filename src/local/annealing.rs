@@ -0,0 +1,181 @@
+//! Simulated annealing, which moves to random neighbouring states, accepting
+//! worse states less often as the search cools.
+
+use super::LocalStats;
+use crate::{state::State, timer::Timed, value::SearchHeuristic};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::marker::PhantomData;
+
+/// A cooling schedule, giving the temperature at each step of simulated
+/// annealing.
+///
+/// This is implemented for functions from the step to the temperature.
+pub trait Schedule {
+  /// The temperature at the given step, where the search stops once it is no
+  /// longer positive
+  fn temperature(&self, step: usize) -> f64;
+}
+
+impl<F: Fn(usize) -> f64> Schedule for F {
+  fn temperature(&self, step: usize) -> f64 {
+    self(step)
+  }
+}
+
+/// Exponential cooling, where the temperature is multiplied by `rate` at each
+/// step.
+#[derive(Clone, Copy, Debug)]
+pub struct Exponential {
+  /// The temperature at the first step
+  pub initial: f64,
+  /// The factor the temperature is multiplied by at each step, below 1
+  pub rate: f64,
+}
+
+impl Schedule for Exponential {
+  fn temperature(&self, step: usize) -> f64 {
+    self.initial * self.rate.powf(step as f64)
+  }
+}
+
+/// Linear cooling, where the temperature falls to 0 after `steps` steps.
+#[derive(Clone, Copy, Debug)]
+pub struct Linear {
+  /// The temperature at the first step
+  pub initial: f64,
+  /// The number of steps until the temperature reaches 0
+  pub steps: usize,
+}
+
+impl Schedule for Linear {
+  fn temperature(&self, step: usize) -> f64 {
+    match self.steps {
+      0 => 0.0,
+      steps => self.initial * (1.0 - step as f64 / steps as f64),
+    }
+  }
+}
+
+/// Logarithmic cooling, where the temperature is inversely proportional to the
+/// log of the step.
+///
+/// This cools slowly enough to be guaranteed to find the best state given long
+/// enough, but that is usually far too long in practice.
+#[derive(Clone, Copy, Debug)]
+pub struct Logarithmic {
+  /// The temperature at the first step
+  pub initial: f64,
+}
+
+impl Schedule for Logarithmic {
+  fn temperature(&self, step: usize) -> f64 {
+    self.initial * 2f64.ln() / (step as f64 + 2.0).ln()
+  }
+}
+
+/// A simulated annealing search, using exponential cooling by default.
+///
+/// At each step, a random action is taken. The new state is accepted if it is
+/// better, and otherwise with probability `exp(-delta / temperature)`, where
+/// `delta` is how much worse it is. This lets the search escape local optima
+/// while it is hot, and settle into one as it cools.
+pub struct SimulatedAnnealing<S, H, C = Exponential> {
+  /// The value of a state, where lower is better
+  pub objective: H,
+  /// The cooling schedule
+  pub schedule: C,
+  /// The maximum number of steps to take
+  pub max_steps: usize,
+  rng: StdRng,
+  stats: LocalStats,
+  _state: PhantomData<S>,
+}
+
+impl<S, H> SimulatedAnnealing<S, H> {
+  /// Creates a new search that cools exponentially from the given temperature,
+  /// at a rate that reaches a thousandth of it after `max_steps` steps
+  pub fn new(objective: H, initial: f64, max_steps: usize) -> Self {
+    let rate = 0.001f64.powf(1.0 / max_steps.max(1) as f64);
+    Self {
+      objective,
+      schedule: Exponential { initial, rate },
+      max_steps,
+      rng: StdRng::from_entropy(),
+      stats: LocalStats::default(),
+      _state: PhantomData,
+    }
+  }
+}
+
+impl<S, H, C> SimulatedAnnealing<S, H, C> {
+  /// Sets the cooling schedule
+  pub fn schedule<C2: Schedule>(self, schedule: C2) -> SimulatedAnnealing<S, H, C2> {
+    SimulatedAnnealing {
+      objective: self.objective,
+      schedule,
+      max_steps: self.max_steps,
+      rng: self.rng,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+
+  /// Seeds the random number generator, so searches can be repeated
+  pub fn seed(mut self, seed: u64) -> Self {
+    self.rng = StdRng::seed_from_u64(seed);
+    self
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &LocalStats {
+    &self.stats
+  }
+}
+
+impl<S: State + Clone, H: SearchHeuristic<S>, C: Schedule> SimulatedAnnealing<S, H, C>
+where
+  H::Cost: Into<f64>,
+{
+  /// Anneals from the given state, returning the best state found and its
+  /// value.
+  pub fn anneal(&mut self, start: S) -> Result<(S, H::Cost), S::Error> {
+    let started = self.stats.start();
+    let result = self.anneal_from(start);
+    self.stats.finish(started);
+    result
+  }
+
+  fn anneal_from(&mut self, start: S) -> Result<(S, H::Cost), S::Error> {
+    let mut value = self.evaluate(&start)?;
+    let mut best = (start.clone(), value.clone());
+    let mut current = start;
+    for step in 0..self.max_steps {
+      let temperature = self.schedule.temperature(step);
+      if temperature <= 0.0 {
+        break;
+      }
+      let actions = current.actions().into_iter().collect::<Vec<_>>();
+      let Some(action) = actions.choose(&mut self.rng) else {
+        break;
+      };
+      let next = current.result(action)?;
+      let next_value = self.evaluate(&next)?;
+
+      let delta = next_value.clone().into() - value.clone().into();
+      if delta > 0.0 && self.rng.gen::<f64>() >= (-delta / temperature).exp() {
+        continue;
+      }
+      if next_value < best.1 {
+        best = (next.clone(), next_value.clone());
+      }
+      (current, value) = (next, next_value);
+      self.stats.steps += 1;
+    }
+    Ok(best)
+  }
+
+  fn evaluate(&mut self, state: &S) -> Result<H::Cost, S::Error> {
+    self.stats.evaluated += 1;
+    Ok(self.objective.value(&state.observe()?))
+  }
+}
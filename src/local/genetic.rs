@@ -0,0 +1,398 @@
+//! A genetic algorithm, which evolves a population of genomes that decode to
+//! states.
+
+use super::LocalStats;
+use crate::{state::State, timer::Timed, value::SearchHeuristic};
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng, RngCore, SeedableRng};
+use std::marker::PhantomData;
+
+/// A candidate solution to be evolved, which decodes to a state.
+///
+/// This is implemented for sequences of actions, which are replayed from the
+/// start, so the same states can be searched with or without a genome.
+pub trait Genome<S: State> {
+  /// Returns the state this genome decodes to, given the start state
+  fn decode(&self, start: &S) -> Result<S, S::Error>;
+}
+
+impl<S: State + Clone> Genome<S> for Vec<S::Action>
+where
+  S::Action: PartialEq,
+{
+  /// Replays the actions from the start, skipping any that aren't valid in
+  /// the state they end up in, as can happen after crossover
+  fn decode(&self, start: &S) -> Result<S, S::Error> {
+    let mut state = start.clone();
+    for action in self {
+      if is_valid(&state, action) {
+        state = state.result(action)?;
+      }
+    }
+    Ok(state)
+  }
+}
+
+/// Whether an action is one of the actions of a state
+fn is_valid<S: State>(state: &S, action: &S::Action) -> bool
+where
+  S::Action: PartialEq,
+{
+  state.actions().into_iter().any(|valid| valid == *action)
+}
+
+/// Returns `true` with the given probability, clamped to between 0 and 1
+fn chance(rng: &mut dyn RngCore, probability: f64) -> bool {
+  rng.gen::<f64>() < probability
+}
+
+/// A way of choosing a parent from the population.
+pub trait ParentSelection {
+  /// Chooses a parent from a population of the given size, sorted from best
+  /// to worst, returning its index
+  fn select(&self, size: usize, rng: &mut dyn RngCore) -> usize;
+}
+
+/// Tournament selection, which chooses the best of `size` random genomes.
+#[derive(Clone, Copy, Debug)]
+pub struct Tournament {
+  /// The number of genomes in each tournament, where more favours the best
+  pub size: usize,
+}
+
+impl Default for Tournament {
+  fn default() -> Self {
+    Self { size: 3 }
+  }
+}
+
+impl ParentSelection for Tournament {
+  fn select(&self, size: usize, rng: &mut dyn RngCore) -> usize {
+    let entrants = (0..self.size.max(1)).map(|_| rng.gen_range(0..size));
+    entrants
+      .min()
+      .expect("there should be at least one entrant")
+  }
+}
+
+/// Truncation selection, which chooses uniformly from the best `fraction` of
+/// the population.
+#[derive(Clone, Copy, Debug)]
+pub struct Truncation {
+  /// The fraction of the population that can be chosen
+  pub fraction: f64,
+}
+
+impl ParentSelection for Truncation {
+  fn select(&self, size: usize, rng: &mut dyn RngCore) -> usize {
+    let best = (size as f64 * self.fraction).ceil() as usize;
+    rng.gen_range(0..best.clamp(1, size))
+  }
+}
+
+/// A way of combining two parents into a child.
+///
+/// This is implemented for functions from the parents to the child.
+pub trait Crossover<G> {
+  /// Returns a child of the two parents
+  fn crossover(&self, a: &G, b: &G, rng: &mut dyn RngCore) -> G;
+}
+
+impl<G, F: Fn(&G, &G, &mut dyn RngCore) -> G> Crossover<G> for F {
+  fn crossover(&self, a: &G, b: &G, rng: &mut dyn RngCore) -> G {
+    self(a, b, rng)
+  }
+}
+
+/// One point crossover, which takes the start of one parent up to a random
+/// point, and the rest of the other.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OnePoint;
+
+impl<A: Clone> Crossover<Vec<A>> for OnePoint {
+  fn crossover(&self, a: &Vec<A>, b: &Vec<A>, rng: &mut dyn RngCore) -> Vec<A> {
+    let point = rng.gen_range(0..=a.len().min(b.len()));
+    a[..point].iter().chain(&b[point..]).cloned().collect()
+  }
+}
+
+/// Uniform crossover, which takes each gene from a random parent.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniformCrossover;
+
+impl<A: Clone> Crossover<Vec<A>> for UniformCrossover {
+  fn crossover(&self, a: &Vec<A>, b: &Vec<A>, rng: &mut dyn RngCore) -> Vec<A> {
+    let length = match rng.gen() {
+      true => a.len(),
+      false => b.len(),
+    };
+    let genes = (0..length).filter_map(|i| match (a.get(i), b.get(i)) {
+      (Some(a), Some(b)) => Some(if rng.gen() { a } else { b }),
+      (a, b) => a.or(b),
+    });
+    genes.cloned().collect()
+  }
+}
+
+/// A way of randomly changing a genome.
+///
+/// This is implemented for functions that change a genome in place.
+pub trait Mutation<S, G> {
+  /// Changes the genome, given the start state it decodes from
+  fn mutate(&self, genome: &mut G, start: &S, rng: &mut dyn RngCore);
+}
+
+impl<S, G, F: Fn(&mut G, &mut dyn RngCore)> Mutation<S, G> for F {
+  fn mutate(&self, genome: &mut G, _start: &S, rng: &mut dyn RngCore) {
+    self(genome, rng)
+  }
+}
+
+/// Mutation of a sequence of actions, which replays the actions and replaces
+/// each with a random valid action with probability `rate` (clamped to between
+/// 0 and 1).
+///
+/// Actions that aren't valid are removed, so the sequence is valid afterwards.
+#[derive(Clone, Copy, Debug)]
+pub struct ActionMutation {
+  /// The probability of replacing each action
+  pub rate: f64,
+}
+
+impl Default for ActionMutation {
+  fn default() -> Self {
+    Self { rate: 0.1 }
+  }
+}
+
+impl<S: State + Clone> Mutation<S, Vec<S::Action>> for ActionMutation
+where
+  S::Action: PartialEq,
+{
+  fn mutate(&self, genome: &mut Vec<S::Action>, start: &S, rng: &mut dyn RngCore) {
+    let mut state = start.clone();
+    let mut mutated = Vec::with_capacity(genome.len());
+    for action in genome.drain(..) {
+      let action = match chance(rng, self.rate) {
+        true => state.actions().into_iter().choose(rng),
+        false => Some(action).filter(|action| is_valid(&state, action)),
+      };
+      let Some(action) = action else {
+        continue;
+      };
+      if let Ok(next) = state.result(&action) {
+        state = next;
+        mutated.push(action);
+      }
+    }
+    *genome = mutated;
+  }
+}
+
+/// A genetic algorithm, using tournament selection, one point crossover and
+/// action mutation by default.
+///
+/// Each generation, the best `elitism` genomes are kept, and the rest of the
+/// population is replaced by children of parents chosen by the selection. A
+/// child is a crossover of its parents with probability `crossover_rate`, or a
+/// copy of the first otherwise, and is then mutated.
+pub struct Genetic<S, G, H, P = Tournament, X = OnePoint, M = ActionMutation> {
+  /// The value of the state a genome decodes to, where lower is better
+  pub objective: H,
+  /// The number of genomes in each generation
+  pub population: usize,
+  /// The number of generations to evolve
+  pub generations: usize,
+  /// The number of the best genomes kept in the next generation unchanged
+  pub elitism: usize,
+  /// The probability that a child is a crossover of its parents, clamped to
+  /// between 0 and 1
+  pub crossover_rate: f64,
+  selection: P,
+  crossover: X,
+  mutation: M,
+  rng: StdRng,
+  stats: LocalStats,
+  _state: PhantomData<(S, G)>,
+}
+
+impl<S, G, H> Genetic<S, G, H> {
+  /// Creates a new genetic algorithm
+  pub fn new(objective: H, population: usize, generations: usize) -> Self {
+    Self {
+      objective,
+      population,
+      generations,
+      elitism: 1,
+      crossover_rate: 0.9,
+      selection: Tournament::default(),
+      crossover: OnePoint,
+      mutation: ActionMutation::default(),
+      rng: StdRng::from_entropy(),
+      stats: LocalStats::default(),
+      _state: PhantomData,
+    }
+  }
+}
+
+impl<S, G, H, P, X, M> Genetic<S, G, H, P, X, M> {
+  /// Sets the number of the best genomes kept in the next generation
+  pub fn elitism(mut self, elitism: usize) -> Self {
+    self.elitism = elitism;
+    self
+  }
+
+  /// Sets the probability that a child is a crossover of its parents
+  pub fn crossover_rate(mut self, crossover_rate: f64) -> Self {
+    self.crossover_rate = crossover_rate;
+    self
+  }
+
+  /// Sets the operator for choosing parents
+  pub fn selection<P2: ParentSelection>(self, selection: P2) -> Genetic<S, G, H, P2, X, M> {
+    Genetic {
+      objective: self.objective,
+      population: self.population,
+      generations: self.generations,
+      elitism: self.elitism,
+      crossover_rate: self.crossover_rate,
+      selection,
+      crossover: self.crossover,
+      mutation: self.mutation,
+      rng: self.rng,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+
+  /// Sets the operator for combining parents
+  pub fn crossover<X2: Crossover<G>>(self, crossover: X2) -> Genetic<S, G, H, P, X2, M> {
+    Genetic {
+      objective: self.objective,
+      population: self.population,
+      generations: self.generations,
+      elitism: self.elitism,
+      crossover_rate: self.crossover_rate,
+      selection: self.selection,
+      crossover,
+      mutation: self.mutation,
+      rng: self.rng,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+
+  /// Sets the operator for changing children
+  pub fn mutation<M2: Mutation<S, G>>(self, mutation: M2) -> Genetic<S, G, H, P, X, M2> {
+    Genetic {
+      objective: self.objective,
+      population: self.population,
+      generations: self.generations,
+      elitism: self.elitism,
+      crossover_rate: self.crossover_rate,
+      selection: self.selection,
+      crossover: self.crossover,
+      mutation,
+      rng: self.rng,
+      stats: self.stats,
+      _state: PhantomData,
+    }
+  }
+
+  /// Seeds the random number generator, so searches can be repeated
+  pub fn seed(mut self, seed: u64) -> Self {
+    self.rng = StdRng::seed_from_u64(seed);
+    self
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &LocalStats {
+    &self.stats
+  }
+}
+
+impl<S: State + Clone, H, P, X, M> Genetic<S, Vec<S::Action>, H, P, X, M> {
+  /// Generates a population of random walks from the start, each taking up
+  /// to `length` actions
+  pub fn random_walks(
+    &mut self,
+    start: &S,
+    length: usize,
+  ) -> Result<Vec<Vec<S::Action>>, S::Error> {
+    let mut population = Vec::with_capacity(self.population);
+    for _ in 0..self.population {
+      let (mut state, mut walk) = (start.clone(), Vec::with_capacity(length));
+      for _ in 0..length {
+        let Some(action) = state.actions().into_iter().choose(&mut self.rng) else {
+          break;
+        };
+        state = state.result(&action)?;
+        walk.push(action);
+      }
+      population.push(walk);
+    }
+    Ok(population)
+  }
+}
+
+impl<S: State, G: Genome<S> + Clone, H: SearchHeuristic<S>, P, X, M> Genetic<S, G, H, P, X, M>
+where
+  P: ParentSelection,
+  X: Crossover<G>,
+  M: Mutation<S, G>,
+{
+  /// Evolves the given population from the start state, returning the best
+  /// genome found and its value, or `None` if the population is empty.
+  pub fn evolve(
+    &mut self,
+    start: &S,
+    population: Vec<G>,
+  ) -> Result<Option<(G, H::Cost)>, S::Error> {
+    let started = self.stats.start();
+    let result = self.evolve_from(start, population);
+    self.stats.finish(started);
+    result
+  }
+
+  fn evolve_from(
+    &mut self,
+    start: &S,
+    population: Vec<G>,
+  ) -> Result<Option<(G, H::Cost)>, S::Error> {
+    if population.is_empty() {
+      return Ok(None);
+    }
+    let mut scored = Vec::with_capacity(population.len());
+    for genome in population {
+      scored.push(self.evaluate(genome, start)?);
+    }
+    scored.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+    for _ in 0..self.generations {
+      let mut next = scored
+        .iter()
+        .take(self.elitism)
+        .cloned()
+        .collect::<Vec<_>>();
+      while next.len() < self.population.max(1) {
+        let a = &scored[self.selection.select(scored.len(), &mut self.rng)].0;
+        let b = &scored[self.selection.select(scored.len(), &mut self.rng)].0;
+        let mut child = match chance(&mut self.rng, self.crossover_rate) {
+          true => self.crossover.crossover(a, b, &mut self.rng),
+          false => a.clone(),
+        };
+        self.mutation.mutate(&mut child, start, &mut self.rng);
+        next.push(self.evaluate(child, start)?);
+      }
+      next.sort_by(|(_, a), (_, b)| a.cmp(b));
+      scored = next;
+      self.stats.steps += 1;
+    }
+    Ok(Some(scored.swap_remove(0)))
+  }
+
+  fn evaluate(&mut self, genome: G, start: &S) -> Result<(G, H::Cost), S::Error> {
+    self.stats.evaluated += 1;
+    let observed = genome.decode(start)?.observe()?;
+    Ok((genome, self.objective.value(&observed)))
+  }
+}
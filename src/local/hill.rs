@@ -0,0 +1,170 @@
+//! Hill climbing, which moves to a better neighbouring state until there are
+//! none left.
+
+use super::LocalStats;
+use crate::{state::State, timer::Timed, value::SearchHeuristic};
+use rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng};
+use std::marker::PhantomData;
+
+/// How hill climbing chooses which neighbouring state to move to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Climb {
+  /// Values every neighbouring state, moving to the best if it is better
+  #[default]
+  Steepest,
+  /// Tries actions in a random order, moving to the first neighbouring state
+  /// that is better, which is quicker for states with many actions
+  FirstChoice,
+}
+
+/// A hill climbing search, using steepest ascent by default.
+///
+/// The search stops once no neighbouring state is better, which may be a
+/// local optimum. Climbing again from random starting states gives more
+/// chances of finding the best state.
+pub struct HillClimbing<S, H> {
+  /// The value of a state, where lower is better
+  pub objective: H,
+  /// How to choose the next state
+  pub strategy: Climb,
+  /// The maximum number of moves to make from each starting state
+  pub max_steps: Option<usize>,
+  rng: StdRng,
+  stats: LocalStats,
+  _state: PhantomData<S>,
+}
+
+impl<S, H> HillClimbing<S, H> {
+  /// Creates a new steepest ascent hill climbing search
+  pub fn new(objective: H) -> Self {
+    Self {
+      objective,
+      strategy: Climb::default(),
+      max_steps: None,
+      rng: StdRng::from_entropy(),
+      stats: LocalStats::default(),
+      _state: PhantomData,
+    }
+  }
+
+  /// Sets how to choose the next state
+  pub fn strategy(mut self, strategy: Climb) -> Self {
+    self.strategy = strategy;
+    self
+  }
+
+  /// Stops climbing from a state after the given number of moves
+  pub fn max_steps(mut self, max_steps: usize) -> Self {
+    self.max_steps = Some(max_steps);
+    self
+  }
+
+  /// Seeds the random number generator, so searches can be repeated
+  pub fn seed(mut self, seed: u64) -> Self {
+    self.rng = StdRng::seed_from_u64(seed);
+    self
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &LocalStats {
+    &self.stats
+  }
+}
+
+impl<S: State, H: SearchHeuristic<S>> HillClimbing<S, H> {
+  /// Climbs from the given state, returning the state it stopped at and its
+  /// value.
+  pub fn climb(&mut self, start: S) -> Result<(S, H::Cost), S::Error> {
+    let started = self.stats.start();
+    let result = self.climb_from(start);
+    self.stats.finish(started);
+    result
+  }
+
+  /// Climbs from each of `restarts` states given by `random_start`, or from
+  /// one if `restarts` is 0, returning the best state found and its value.
+  pub fn climb_with_restarts<R>(
+    &mut self,
+    restarts: usize,
+    mut random_start: R,
+  ) -> Result<(S, H::Cost), S::Error>
+  where
+    R: FnMut(&mut dyn RngCore) -> S,
+  {
+    let started = self.stats.start();
+    let mut best: Option<(S, H::Cost)> = None;
+    let mut result = Ok(());
+    for restart in 0..restarts.max(1) {
+      if restart > 0 {
+        self.stats.restarts += 1;
+      }
+      let start = random_start(&mut self.rng);
+      match self.climb_from(start) {
+        Ok((state, value)) => {
+          if best.as_ref().is_none_or(|(_, best)| value < *best) {
+            best = Some((state, value));
+          }
+        }
+        Err(err) => {
+          result = Err(err);
+          break;
+        }
+      }
+    }
+    self.stats.finish(started);
+    result.map(|_| best.expect("there should be at least one climb"))
+  }
+
+  fn climb_from(&mut self, start: S) -> Result<(S, H::Cost), S::Error> {
+    let mut value = self.evaluate(&start)?;
+    let mut current = start;
+    let mut steps = 0;
+    while self.max_steps.is_none_or(|max_steps| steps < max_steps) {
+      let next = match self.strategy {
+        Climb::Steepest => self.steepest(&current, &value)?,
+        Climb::FirstChoice => self.first_choice(&current, &value)?,
+      };
+      let Some((next, next_value)) = next else {
+        break;
+      };
+      (current, value) = (next, next_value);
+      self.stats.steps += 1;
+      steps += 1;
+    }
+    Ok((current, value))
+  }
+
+  /// The best neighbouring state, if it is better than the current one
+  fn steepest(&mut self, state: &S, value: &H::Cost) -> Result<Option<(S, H::Cost)>, S::Error> {
+    let mut best: Option<(S, H::Cost)> = None;
+    for action in state.actions() {
+      let next = state.result(&action)?;
+      let next_value = self.evaluate(&next)?;
+      let threshold = best.as_ref().map_or(value, |(_, best)| best);
+      if next_value < *threshold {
+        best = Some((next, next_value));
+      }
+    }
+    Ok(best)
+  }
+
+  /// The first neighbouring state that is better than the current one, trying
+  /// actions in a random order
+  fn first_choice(&mut self, state: &S, value: &H::Cost) -> Result<Option<(S, H::Cost)>, S::Error> {
+    let mut actions = state.actions().into_iter().collect::<Vec<_>>();
+    actions.shuffle(&mut self.rng);
+    for action in actions {
+      let next = state.result(&action)?;
+      let next_value = self.evaluate(&next)?;
+      if next_value < *value {
+        return Ok(Some((next, next_value)));
+      }
+    }
+    Ok(None)
+  }
+
+  fn evaluate(&mut self, state: &S) -> Result<H::Cost, S::Error> {
+    self.stats.evaluated += 1;
+    Ok(self.objective.value(&state.observe()?))
+  }
+}
@@ -0,0 +1,21 @@
+//! Local search, for optimisation problems with no goal to find.
+//!
+//! Rather than finding a path to a goal state, these searches move from state
+//! to state looking for the best state they can find, as valued by an
+//! objective. As with heuristics, lower values of the objective are better.
+//! Only the current state is kept, so they run in constant memory, but may
+//! stop at a local optimum rather than the best state.
+
+mod annealing;
+mod genetic;
+mod hill;
+mod stats;
+mod tabu;
+pub use annealing::{Exponential, Linear, Logarithmic, Schedule, SimulatedAnnealing};
+pub use genetic::{
+  ActionMutation, Crossover, Genetic, Genome, Mutation, OnePoint, ParentSelection, Tournament,
+  Truncation, UniformCrossover,
+};
+pub use hill::{Climb, HillClimbing};
+pub use stats::LocalStats;
+pub use tabu::TabuSearch;
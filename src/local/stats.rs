@@ -0,0 +1,21 @@
+use crate::timer::Timed;
+use std::time::Duration;
+
+/// Statistics on the work done by the last call to a local search.
+#[derive(Clone, Debug, Default)]
+pub struct LocalStats {
+  /// The number of moves made from one state to another
+  pub steps: usize,
+  /// The number of states valued by the objective
+  pub evaluated: usize,
+  /// The number of times the search started again from a new state
+  pub restarts: usize,
+  /// The time taken by the search
+  pub elapsed: Duration,
+}
+
+impl Timed for LocalStats {
+  fn elapsed_mut(&mut self) -> &mut Duration {
+    &mut self.elapsed
+  }
+}
@@ -0,0 +1,133 @@
+//! Tabu search, which always moves to the best neighbouring state that hasn't
+//! been visited recently.
+
+use super::LocalStats;
+use crate::{state::State, timer::Timed, value::SearchHeuristic};
+use std::{
+  collections::{HashSet, VecDeque},
+  hash::Hash,
+  marker::PhantomData,
+};
+
+/// A tabu search.
+///
+/// At each step, the search moves to the best neighbouring state, even if it
+/// is worse, so it can walk out of local optima. To stop it walking straight
+/// back, the observations of the last `tenure` states are tabu, and states
+/// with a tabu observation are skipped unless they are better than any state
+/// found so far.
+pub struct TabuSearch<S, H> {
+  /// The value of a state, where lower is better
+  pub objective: H,
+  /// The number of recent observations that are tabu
+  pub tenure: usize,
+  /// The maximum number of steps to take
+  pub max_steps: usize,
+  stats: LocalStats,
+  _state: PhantomData<S>,
+}
+
+impl<S, H> TabuSearch<S, H> {
+  /// Creates a new tabu search
+  pub fn new(objective: H, tenure: usize, max_steps: usize) -> Self {
+    Self {
+      objective,
+      tenure,
+      max_steps,
+      stats: LocalStats::default(),
+      _state: PhantomData,
+    }
+  }
+
+  /// Statistics on the work done by the last search
+  pub fn stats(&self) -> &LocalStats {
+    &self.stats
+  }
+}
+
+impl<S: State + Clone, H: SearchHeuristic<S>> TabuSearch<S, H>
+where
+  S::Observation: Hash + Eq + Clone,
+{
+  /// Searches from the given state, returning the best state found and its
+  /// value.
+  pub fn search(&mut self, start: S) -> Result<(S, H::Cost), S::Error> {
+    let started = self.stats.start();
+    let result = self.search_from(start);
+    self.stats.finish(started);
+    result
+  }
+
+  fn search_from(&mut self, start: S) -> Result<(S, H::Cost), S::Error> {
+    let observed = start.observe()?;
+    self.stats.evaluated += 1;
+    let mut best = (start.clone(), self.objective.value(&observed));
+    let mut current = start;
+    let mut tabu = Tabu::default();
+    tabu.push(observed, self.tenure);
+
+    for _ in 0..self.max_steps {
+      let mut next: Option<(S, S::Observation, H::Cost)> = None;
+      for action in current.actions() {
+        let state = current.result(&action)?;
+        let observed = state.observe()?;
+        self.stats.evaluated += 1;
+        let value = self.objective.value(&observed);
+
+        // A tabu state is still allowed if it's the best found so far
+        if tabu.contains(&observed) && value >= best.1 {
+          continue;
+        }
+        if next.as_ref().is_none_or(|(_, _, next)| value < *next) {
+          next = Some((state, observed, value));
+        }
+      }
+      let Some((state, observed, value)) = next else {
+        break;
+      };
+
+      tabu.push(observed, self.tenure);
+      if value < best.1 {
+        best = (state.clone(), value);
+      }
+      current = state;
+      self.stats.steps += 1;
+    }
+    Ok(best)
+  }
+}
+
+/// The observations that are tabu, in the order they were added
+struct Tabu<O> {
+  order: VecDeque<O>,
+  set: HashSet<O>,
+}
+
+impl<O> Default for Tabu<O> {
+  fn default() -> Self {
+    Self {
+      order: VecDeque::new(),
+      set: HashSet::new(),
+    }
+  }
+}
+
+impl<O: Hash + Eq + Clone> Tabu<O> {
+  fn contains(&self, observed: &O) -> bool {
+    self.set.contains(observed)
+  }
+
+  /// Makes an observation tabu, keeping only the most recent `tenure`. An
+  /// observation that is already tabu becomes the most recent again.
+  fn push(&mut self, observed: O, tenure: usize) {
+    if !self.set.insert(observed.clone()) {
+      self.order.retain(|tabu| *tabu != observed);
+    }
+    self.order.push_back(observed);
+    while self.order.len() > tenure {
+      if let Some(oldest) = self.order.pop_front() {
+        self.set.remove(&oldest);
+      }
+    }
+  }
+}
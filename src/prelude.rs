@@ -1,5 +1,10 @@
 pub use crate::{
-  search::{Astar, Bfs, Dfs, Dls, Greedy, Ids, Uniform},
+  search::{
+    Ara, Astar, Beam, BeamRank, Bfs, BidirectionalAstar, BidirectionalBfs, Dfs, Dls, ErrorPolicy,
+    Greedy, Hda, Ida, Ids, Limit, Portfolio, Rbfs, SearchLimits, SearchStats, SmaStar, Solution,
+    Uniform,
+  },
   traits::*,
+  value::Weighted,
   wrappers::{DepthState, GraphState, HistoryState, LoggingState, PathCostState},
 };
@@ -0,0 +1,249 @@
+use super::{tree::SearchTree, SearchStats, Solution, State};
+use crate::value::{SearchCost, SearchHeuristic};
+use priority_queue::PriorityQueue;
+use std::{
+  cmp::Reverse,
+  collections::{HashSet, VecDeque},
+  hash::Hash,
+  ops::Mul,
+};
+
+/// An Anytime repairing A* search.
+///
+/// This performs a series of weighted A* searches (see `Weighted`), using each
+/// weight from a decreasing schedule in turn. Rather than starting from scratch
+/// for each weight, the path costs found so far are reused, and only states
+/// whose path cost has improved since they were expanded are explored again.
+///
+/// Unlike the other searches, this iterates over solutions rather than states,
+/// yielding the best solution found so far after each weight, along with the
+/// weight. The cost of each solution is at most `weight` times the optimal
+/// cost, and its `lower_bound` is often much tighter, equalling its cost once
+/// it's proven optimal. This gives a fast first answer whose cost and bound
+/// improve the longer the search is run.
+pub struct Ara<S: State, C, H, W, G>
+where
+  S: Hash + Eq,
+  C: SearchCost<S>,
+  H: SearchHeuristic<S, Cost = C::Cost>,
+{
+  start: S,
+  states: PriorityQueue<S, Reverse<C::Cost>>,
+  tree: SearchTree<S, (C::Cost, C::Cost)>,
+  closed: HashSet<usize>,
+  inconsistent: HashSet<S>,
+  errors: VecDeque<S::Error>,
+  action_cost: C,
+  heuristic: H,
+  weight: Option<W>,
+  weights: VecDeque<W>,
+  goal: G,
+  incumbent: Option<usize>,
+  stats: SearchStats,
+}
+
+impl<S: State, C, H, W, G> Ara<S, C, H, W, G>
+where
+  S: Hash + Eq + Clone,
+  C: SearchCost<S>,
+  H: SearchHeuristic<S, Cost = C::Cost>,
+  C::Cost: Mul<W, Output = C::Cost>,
+  W: Clone,
+  G: Fn(&S::Observation) -> bool,
+{
+  /// Creates a new search for a state satisfying `goal`.
+  ///
+  /// `weights` should be decreasing, and end with a weight of 1 if the search
+  /// should eventually find the optimal solution.
+  pub fn new(
+    start: S,
+    action_cost: C,
+    heuristic: H,
+    weights: impl IntoIterator<Item = W>,
+    goal: G,
+  ) -> Self {
+    let mut weights: VecDeque<_> = weights.into_iter().collect();
+    let mut search = Self {
+      start: start.clone(),
+      states: PriorityQueue::new(),
+      tree: SearchTree::new(start.clone(), Default::default()),
+      closed: HashSet::new(),
+      inconsistent: HashSet::new(),
+      errors: VecDeque::new(),
+      action_cost,
+      heuristic,
+      weight: weights.pop_front(),
+      weights,
+      goal,
+      incumbent: None,
+      stats: SearchStats::default(),
+    };
+
+    match start.observe() {
+      Ok(obs) => {
+        let value = search.heuristic.value(&obs);
+        let id = search
+          .tree
+          .insert(start.clone(), None, (Default::default(), value));
+        if (search.goal)(&obs) {
+          search.incumbent = Some(id);
+        }
+        if let Some(priority) = search.priority(id) {
+          search.states.push(start, priority);
+        }
+      }
+      Err(e) => search.errors.push_back(e.into()),
+    }
+    search
+  }
+
+  /// Statistics on the work done over all weights so far
+  pub fn stats(&self) -> &SearchStats {
+    &self.stats
+  }
+
+  /// The weighted f-value of the state with the given id
+  fn priority(&self, id: usize) -> Option<Reverse<C::Cost>> {
+    let (path_cost, value) = self.tree.node(id).cost.clone();
+    Some(Reverse(path_cost + value * self.weight.clone()?))
+  }
+
+  /// Whether the current solution is proven to be within the current weight
+  /// of the optimal solution
+  fn improved(&self) -> bool {
+    let Some((_, min)) = self.states.peek() else {
+      return true;
+    };
+    self
+      .incumbent
+      .and_then(|id| self.priority(id))
+      .is_some_and(|incumbent| &incumbent >= min)
+  }
+
+  fn expand(&mut self) {
+    let Some((state, _)) = self.states.pop() else {
+      return;
+    };
+    let Some(id) = self.tree.id(&state) else {
+      return;
+    };
+    self.closed.insert(id);
+    let path_cost = self.tree.node(id).cost.0.clone();
+
+    self.stats.expand(self.tree.node(id).depth);
+    for action in state.actions() {
+      self.stats.generate();
+      let new_state = match state.result(&action) {
+        Ok(new_state) => new_state,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      let new_cost = path_cost.clone() + self.action_cost.cost(&action);
+      let known = self.tree.id(&new_state);
+      if known.is_some_and(|id| self.tree.node(id).cost.0 <= new_cost) {
+        self.stats.duplicate();
+        continue;
+      }
+
+      let obs = match new_state.observe() {
+        Ok(obs) => obs,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      let value = self.heuristic.value(&obs);
+      let is_better = (self.goal)(&obs)
+        && self
+          .incumbent
+          .is_none_or(|id| new_cost < self.tree.node(id).cost.0);
+      let new_id = self
+        .tree
+        .insert(new_state.clone(), Some((id, action)), (new_cost, value));
+      if is_better {
+        self.incumbent = Some(new_id);
+      }
+
+      if self.closed.contains(&new_id) {
+        self.inconsistent.insert(new_state);
+      } else if let Some(priority) = self.priority(new_id) {
+        self.states.push(new_state, priority);
+      }
+    }
+    self
+      .stats
+      .frontier(self.states.len() + self.inconsistent.len());
+  }
+
+  /// The smallest unweighted f-value of any state that may still improve the
+  /// current solution
+  fn lower_bound(&self, cost: C::Cost) -> C::Cost {
+    let states = self.states.iter().map(|(state, _)| state);
+    states
+      .chain(&self.inconsistent)
+      .filter_map(|state| self.tree.id(state))
+      .map(|id| {
+        let (path_cost, value) = self.tree.node(id).cost.clone();
+        path_cost + value
+      })
+      .fold(cost, |bound, value| bound.min(value))
+  }
+
+  /// Moves onto the next weight, reopening all inconsistent states
+  fn next_weight(&mut self) {
+    self.weight = self.weights.pop_front();
+    self.closed.clear();
+    let mut states = std::mem::replace(&mut self.states, PriorityQueue::new()).into_vec();
+    states.extend(self.inconsistent.drain());
+    for state in states {
+      let priority = self.tree.id(&state).and_then(|id| self.priority(id));
+      if let Some(priority) = priority {
+        self.states.push(state, priority);
+      }
+    }
+  }
+}
+
+impl<S: State, C, H, W, G> Iterator for Ara<S, C, H, W, G>
+where
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
+  C: SearchCost<S>,
+  H: SearchHeuristic<S, Cost = C::Cost>,
+  C::Cost: Mul<W, Output = C::Cost>,
+  W: Clone,
+  G: Fn(&S::Observation) -> bool,
+{
+  type Item = Result<(W, Solution<S, C::Cost>), S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(e) = self.errors.pop_front() {
+        return Some(Err(e));
+      }
+      let weight = self.weight.clone()?;
+      if !self.improved() {
+        self.expand();
+        continue;
+      }
+
+      let solution = self.incumbent.map(|id| {
+        // Paths to earlier states may have improved since the goal was reached
+        let actions = self.tree.actions(id);
+        let cost = (actions.iter()).fold(C::Cost::default(), |cost, action| {
+          cost + self.action_cost.cost(action)
+        });
+        let lower_bound = self.lower_bound(cost.clone());
+        Solution::new(self.start.clone(), actions, cost)
+          .with_lower_bound(lower_bound)
+          .with_stats(Some(&self.stats))
+      });
+      self.next_weight();
+      if let Some(solution) = solution {
+        return Some(Ok((weight, solution)));
+      }
+    }
+  }
+}
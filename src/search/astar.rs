@@ -1,50 +1,139 @@
-use super::{Search, State};
+use super::{
+  helpers::FValue,
+  trail::{CostTrailState, Trail},
+  Search, SearchStats, Solution, State,
+};
 use crate::{
-  value::{SearchCost, SearchHeuristic},
+  value::{SearchCost, SearchHeuristic, Weighted},
   wrappers::{PathCostState, StateWrapper},
 };
 use priority_queue::PriorityQueue;
-use std::{cmp::Reverse, hash::Hash, ops::Add};
+use std::{
+  cmp::Reverse,
+  collections::{HashMap, VecDeque},
+  hash::Hash,
+  ops::{Add, Mul},
+};
 
-/// A Greedy traversal of possible states.
+/// An A* traversal of possible states.
+///
+/// Will explore states with the lowest path cost + heuristic value first.
 ///
-/// Will explore states with the lowest heuristic value first.
+/// The best known path cost to each state is kept, and a state is only queued
+/// again when a cheaper path to it is found, reopening it if it has already
+/// been explored. This makes the search optimal for any admissible heuristic,
+/// even an inconsistent one, without needing to wrap states in `GraphState`.
 pub struct Astar<S: State, C: SearchCost<S>, H: SearchHeuristic<S>>
 where
   S: Hash + Eq,
+  C: Clone,
   C::Cost: Hash + Add<H::Cost>,
   <C::Cost as Add<H::Cost>>::Output: Ord,
 {
-  states: PriorityQueue<PathCostState<S, C>, Reverse<<C::Cost as Add<H::Cost>>::Output>>,
+  states: PriorityQueue<CostTrailState<S, C>, Reverse<FValue<S, C, H>>>,
+  /// The best known path cost to each state, and whether it has been explored
+  costs: HashMap<S, (C::Cost, bool)>,
+  errors: VecDeque<S::Error>,
+  start: S,
+  last: (Trail<S::Action>, C::Cost),
+  stats: SearchStats,
   action_cost: C,
   heuristic: H,
 }
 
 impl<S: State, C: SearchCost<S> + Clone, H: SearchHeuristic<S>> Astar<S, C, H>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
   C::Cost: Hash + Add<H::Cost>,
   <C::Cost as Add<H::Cost>>::Output: Ord,
 {
+  /// Creates a new search from the given start state.
+  ///
+  /// If the start state can't be observed, the error is yielded by the search.
   pub fn new(start: S, action_cost: C, heuristic: H) -> Self {
-    let mut states = PriorityQueue::new();
-    let obs = start.observe().map_err(S::Error::from).unwrap();
-    let cost = heuristic.value(&obs);
-    states.push(
-      PathCostState::new(start, action_cost.clone()),
-      Reverse(<C::Cost as Add<H::Cost>>::add(Default::default(), cost)),
-    );
+    let mut search = Self::empty(start.clone(), action_cost, heuristic);
+    if let Err(e) = search.reset(start) {
+      search.errors.push_back(e);
+    }
+    search
+  }
+
+  /// Creates a new search from the given start state, failing if the start
+  /// state can't be observed.
+  pub fn try_new(start: S, action_cost: C, heuristic: H) -> Result<Self, S::Error> {
+    let mut search = Self::empty(start.clone(), action_cost, heuristic);
+    search.reset(start)?;
+    Ok(search)
+  }
+
+  fn empty(start: S, action_cost: C, heuristic: H) -> Self {
     Self {
-      states,
+      states: PriorityQueue::new(),
+      costs: HashMap::new(),
+      errors: VecDeque::new(),
+      start,
+      last: Default::default(),
+      stats: SearchStats::default(),
       action_cost,
       heuristic,
     }
   }
+
+  fn reset(&mut self, start: S) -> Result<(), S::Error> {
+    self.states.clear();
+    self.errors.clear();
+    self.last = Default::default();
+    self.stats = SearchStats::default();
+    self.start = start.clone();
+    self.costs = HashMap::from([(start.clone(), Default::default())]);
+    let cost = self.heuristic.value(&start.observe()?);
+    self.states.push(
+      PathCostState::new(start, self.action_cost.clone()).into(),
+      Reverse(<C::Cost as Add<H::Cost>>::add(Default::default(), cost)),
+    );
+    Ok(())
+  }
+
+  /// Records the path cost of a newly reached state, returning `false` if a
+  /// path at least as cheap is already known.
+  ///
+  /// Explored states are reopened when a cheaper path to them is found.
+  fn improve(&mut self, state: &PathCostState<S, C>) -> bool {
+    let (state, path_cost) = (state.clone().unwrap(), state.path_cost());
+    match self.costs.get(&state) {
+      Some((cost, _)) if cost <= &path_cost => {
+        self.stats.duplicate();
+        return false;
+      }
+      Some((_, true)) => self.stats.reopen(),
+      _ => {}
+    }
+    self.costs.insert(state, (path_cost, false));
+    true
+  }
+}
+
+impl<S: State, C: SearchCost<S> + Clone, H: SearchHeuristic<S>, W: Clone>
+  Astar<S, C, Weighted<H, W>>
+where
+  S: Hash + Eq + Clone,
+  H::Cost: Mul<W, Output = H::Cost>,
+  C::Cost: Hash + Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord,
+{
+  /// Creates a weighted A* search, which explores states with the lowest
+  /// `path cost + weight * heuristic` first.
+  ///
+  /// See `Weighted` for the trade-off this makes.
+  pub fn weighted(start: S, action_cost: C, heuristic: H, weight: W) -> Self {
+    Self::new(start, action_cost, Weighted::new(heuristic, weight))
+  }
 }
 
 impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Iterator for Astar<S, C, H>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
   C: Clone,
   C::Cost: Hash + Add<H::Cost>,
   <C::Cost as Add<H::Cost>>::Output: Ord,
@@ -52,34 +141,69 @@ where
   type Item = Result<S, S::Error>;
 
   fn next(&mut self) -> Option<Self::Item> {
+    if let Some(e) = self.errors.pop_front() {
+      return Some(Err(e));
+    }
     let (state, _) = self.states.pop()?;
+    if let Some((_, explored)) = self.costs.get_mut(state.state().inner()) {
+      *explored = true;
+    }
 
-    let actions = state.actions().into_iter().filter_map(|action| {
-      let new_state = state.result(&action).ok()?;
-      let state_value = self.heuristic.value(&new_state.observe().ok()?);
-      let state_astar = <C::Cost as Add<H::Cost>>::add(new_state.path_cost(), state_value);
-      Some((new_state, Reverse(state_astar)))
-    });
+    self.stats.expand(state.depth());
+    for action in state.actions() {
+      self.stats.generate();
+      let new_state = match state.result(&action) {
+        Ok(new_state) => new_state,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      let obs = match new_state.observe() {
+        Ok(obs) => obs,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      if !self.improve(new_state.state()) {
+        continue;
+      }
+      let state_value = self.heuristic.value(&obs);
+      let path_cost = new_state.state().path_cost();
+      let state_astar = <C::Cost as Add<H::Cost>>::add(path_cost, state_value);
+      // Pushing an existing state would keep its old path cost
+      self.states.remove(&new_state);
+      self.states.push(new_state, Reverse(state_astar));
+    }
 
-    self.states.extend(actions);
-    Some(Ok(state.unwrap()))
+    self.stats.frontier(self.states.len());
+    self.last = (state.trail().clone(), state.state().path_cost());
+    Some(Ok(state.unwrap().unwrap()))
   }
 }
 
 impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Search<S> for Astar<S, C, H>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
   C: Clone,
   C::Cost: Hash + Add<H::Cost>,
   <C::Cost as Add<H::Cost>>::Output: Ord,
 {
+  type Cost = C::Cost;
+
   fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
-    self.states.clear();
-    let cost = self.heuristic.value(&start.observe()?);
-    self.states.push(
-      PathCostState::new(start, self.action_cost.clone()),
-      Reverse(<C::Cost as Add<H::Cost>>::add(Default::default(), cost)),
-    );
-    Ok(())
+    self.reset(start)
+  }
+
+  fn solution(&self) -> Option<Solution<S, C::Cost>> {
+    let (trail, cost) = self.last.clone();
+    let solution = Solution::new(self.start.clone(), trail.actions(), cost);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
   }
 }
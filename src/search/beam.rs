@@ -0,0 +1,275 @@
+use super::{
+  trail::{CostTrailState, Trail},
+  Search, SearchStats, Solution, State,
+};
+use crate::{
+  value::{SearchCost, SearchHeuristic},
+  wrappers::{PathCostState, StateWrapper},
+};
+use std::{
+  collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+  hash::Hash,
+};
+
+/// How states are ranked when choosing which to keep in a beam.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BeamRank {
+  /// Keep the states with the lowest heuristic value, as in `Greedy`
+  Heuristic,
+  /// Keep the states with the lowest path cost + heuristic, as in `Astar`
+  #[default]
+  FValue,
+}
+
+/// A Beam traversal of possible states.
+///
+/// Explores states one depth at a time (like `Bfs`), but only keeps the best
+/// `width` states at each depth, so will never store more than `width` states
+/// per depth. This means it may miss the goal entirely, as the states leading
+/// to it may be dropped from the beam.
+///
+/// With backtracking enabled, this becomes a beam-stack search. Whenever a beam
+/// runs out of states, the search backtracks to the deepest depth with states
+/// that were dropped and tries the next best `width` of them. This is complete
+/// for finite state spaces, whilst still only storing `width` states for each
+/// depth of the current path.
+///
+/// Successors already in one of the stored beams are dropped, so the search
+/// can't go round a cycle forever. Without backtracking only the current beam
+/// is stored, which stops the search from undoing its last action.
+///
+/// Regenerating a beam when backtracking takes the same actions again, so any
+/// errors are yielded again too.
+pub struct Beam<S: State, C: SearchCost<S>, H: SearchHeuristic<S, Cost = C::Cost>>
+where
+  C: Clone,
+{
+  layers: Vec<(Vec<CostTrailState<S, C>>, usize)>,
+  in_layers: HashSet<S>,
+  start: S,
+  last: (Trail<S::Action>, C::Cost),
+  cursor: usize,
+  depth: usize,
+  errors: VecDeque<S::Error>,
+  action_cost: C,
+  heuristic: H,
+  width: usize,
+  rank: BeamRank,
+  backtrack: bool,
+  stats: SearchStats,
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S, Cost = C::Cost>> Beam<S, C, H>
+where
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
+  C: Clone,
+{
+  /// Creates a new search that keeps the best `width` states at each depth,
+  /// ranked by f-value and without backtracking.
+  pub fn new(start: S, action_cost: C, heuristic: H, width: usize) -> Self {
+    Self {
+      layers: vec![(
+        vec![PathCostState::new(start.clone(), action_cost.clone()).into()],
+        1,
+      )],
+      in_layers: HashSet::from([start.clone()]),
+      start,
+      last: Default::default(),
+      cursor: 0,
+      depth: 0,
+      errors: VecDeque::new(),
+      action_cost,
+      heuristic,
+      width,
+      rank: BeamRank::default(),
+      backtrack: false,
+      stats: SearchStats::default(),
+    }
+  }
+
+  /// Sets how states are ranked when choosing which to keep
+  pub fn rank_by(mut self, rank: BeamRank) -> Self {
+    self.rank = rank;
+    self
+  }
+
+  /// Enables backtracking to states dropped from the beam, making the search
+  /// complete (see `Beam`).
+  pub fn with_backtracking(mut self) -> Self {
+    self.backtrack = true;
+    self
+  }
+
+  /// Generates the next beam from the given layer (at the current depth),
+  /// skipping the best `offset` states as they have already been explored.
+  fn successors(&mut self, layer: usize, offset: usize) -> Vec<CostTrailState<S, C>> {
+    let mut ids: HashMap<S, usize> = HashMap::new();
+    let mut values: Vec<C::Cost> = vec![];
+    let mut states: Vec<Option<CostTrailState<S, C>>> = vec![];
+    for state in &self.layers[layer].0 {
+      self.stats.expand(self.depth);
+      for action in state.actions() {
+        self.stats.generate();
+        let new_state = match state.result(&action) {
+          Ok(new_state) => new_state,
+          Err(e) => {
+            self.errors.push_back(e.into());
+            continue;
+          }
+        };
+        let key = new_state.state().clone().unwrap();
+        if self.in_layers.contains(&key) {
+          self.stats.duplicate();
+          continue;
+        }
+        let value = match new_state.observe() {
+          Ok(obs) => self.heuristic.value(&obs),
+          Err(e) => {
+            self.errors.push_back(e.into());
+            continue;
+          }
+        };
+        let value = match self.rank {
+          BeamRank::Heuristic => value,
+          BeamRank::FValue => new_state.state().path_cost() + value,
+        };
+
+        match ids.entry(key) {
+          Entry::Occupied(entry) if value < values[*entry.get()] => {
+            self.stats.duplicate();
+            values[*entry.get()] = value;
+            states[*entry.get()] = Some(new_state);
+          }
+          Entry::Occupied(_) => self.stats.duplicate(),
+          Entry::Vacant(entry) => {
+            entry.insert(states.len());
+            values.push(value);
+            states.push(Some(new_state));
+          }
+        }
+      }
+    }
+
+    // The sort is stable, so ties are broken by the order states were generated
+    // in, meaning the same beam is produced when regenerating it
+    let mut order: Vec<usize> = (0..states.len()).collect();
+    order.sort_by(|&lhs, &rhs| values[lhs].cmp(&values[rhs]));
+    let order = order.into_iter().skip(offset).take(self.width);
+    order.filter_map(|id| states[id].take()).collect()
+  }
+
+  /// Moves onto the next beam to explore, returning `false` if there are none.
+  fn next_layer(&mut self) -> bool {
+    let beam = self.successors(self.layers.len() - 1, 0);
+    if !beam.is_empty() {
+      // Earlier beams are only needed to regenerate states when backtracking
+      if !self.backtrack {
+        self.layers.clear();
+        self.in_layers.clear();
+      }
+      let explored = beam.len();
+      self.push_layer(beam, explored);
+      return true;
+    }
+    if !self.backtrack {
+      return false;
+    }
+
+    while let Some((beam, offset)) = self.layers.pop() {
+      for state in beam {
+        self.in_layers.remove(&state.unwrap().unwrap());
+      }
+      if self.layers.is_empty() {
+        break;
+      }
+      self.depth -= 1;
+      let beam = self.successors(self.layers.len() - 1, offset);
+      if !beam.is_empty() {
+        let explored = offset + beam.len();
+        self.push_layer(beam, explored);
+        return true;
+      }
+    }
+    false
+  }
+
+  /// Stores a new beam below the current one, with the number of states in
+  /// the full layer that have been explored.
+  fn push_layer(&mut self, beam: Vec<CostTrailState<S, C>>, explored: usize) {
+    let states = beam.iter().map(|state| state.state().clone().unwrap());
+    self.in_layers.extend(states);
+    self.layers.push((beam, explored));
+    self.depth += 1;
+    self.stored();
+  }
+
+  /// Records the number of states stored in all beams
+  fn stored(&mut self) {
+    let stored = self.layers.iter().map(|(beam, _)| beam.len()).sum();
+    self.stats.frontier(stored);
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S, Cost = C::Cost>> Iterator for Beam<S, C, H>
+where
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
+  C: Clone,
+{
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(e) = self.errors.pop_front() {
+        return Some(Err(e));
+      }
+
+      let (beam, _) = self.layers.last()?;
+      if let Some(state) = beam.get(self.cursor) {
+        self.cursor += 1;
+        let state = state.clone();
+        self.last = (state.trail().clone(), state.state().path_cost());
+        return Some(Ok(state.unwrap().unwrap()));
+      }
+
+      self.cursor = 0;
+      if !self.next_layer() {
+        self.layers.clear();
+      }
+    }
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S, Cost = C::Cost>> Search<S> for Beam<S, C, H>
+where
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
+  C: Clone,
+{
+  type Cost = C::Cost;
+
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    let state = PathCostState::new(start.clone(), self.action_cost.clone());
+    self.layers.clear();
+    self.layers.push((vec![state.into()], 1));
+    self.in_layers = HashSet::from([start.clone()]);
+    self.start = start;
+    self.last = Default::default();
+    self.cursor = 0;
+    self.depth = 0;
+    self.errors.clear();
+    self.stats = SearchStats::default();
+    Ok(())
+  }
+
+  fn solution(&self) -> Option<Solution<S, C::Cost>> {
+    let (trail, cost) = self.last.clone();
+    let solution = Solution::new(self.start.clone(), trail.actions(), cost);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
+}
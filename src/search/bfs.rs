@@ -1,4 +1,8 @@
-use super::{Search, State};
+use super::{
+  trail::{Trail, TrailState},
+  Search, SearchStats, Solution, State,
+};
+use crate::wrappers::StateWrapper;
 use std::collections::VecDeque;
 
 /// A Breadth first traversal of possible states.
@@ -6,41 +10,78 @@ use std::collections::VecDeque;
 /// Will only explore states at a given depth after all states at the previous
 /// depth have been explored.
 pub struct Bfs<S: State> {
-  states: VecDeque<Result<S, S::Error>>,
+  states: VecDeque<Result<TrailState<S>, S::Error>>,
+  start: S,
+  last: Trail<S::Action>,
+  stats: SearchStats,
 }
 
-impl<S: State> Bfs<S> {
+impl<S: State> Bfs<S>
+where
+  S: Clone,
+{
   pub fn new(start: S) -> Self {
     let mut states = VecDeque::new();
-    states.push_back(Ok(start));
-    Self { states }
+    states.push_back(Ok(start.clone().into()));
+    Self {
+      states,
+      start,
+      last: Trail::default(),
+      stats: SearchStats::default(),
+    }
   }
 }
 
-impl<S: State> Iterator for Bfs<S> {
+impl<S: State> Iterator for Bfs<S>
+where
+  S::Action: Clone,
+{
   type Item = Result<S, S::Error>;
 
   fn next(&mut self) -> Option<Self::Item> {
     let result = self.states.pop_front()?;
     let state = match result {
       Ok(state) => state,
-      Err(_) => return Some(result),
+      Err(e) => return Some(Err(e)),
     };
 
-    let actions = state
-      .actions()
-      .into_iter()
-      .map(|action| state.result(&action).map_err(|e| e.into()));
+    self.stats.expand(state.depth());
+    let actions = state.actions().into_iter().map(|action| {
+      let result = state.result(&action).map_err(S::Error::from);
+      self.stats.generate();
+      result
+    });
 
     self.states.extend(actions);
-    Some(Ok(state))
+    self.stats.frontier(self.states.len());
+    self.last = state.trail().clone();
+    Some(Ok(state.unwrap()))
   }
 }
 
-impl<S: State> Search<S> for Bfs<S> {
+impl<S: State> Search<S> for Bfs<S>
+where
+  S: Clone,
+  S::Action: Clone,
+{
+  type Cost = usize;
+
   fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
     self.states.clear();
-    self.states.push_back(Ok(start));
+    self.states.push_back(Ok(start.clone().into()));
+    self.start = start;
+    self.last = Trail::default();
+    self.stats = SearchStats::default();
     Ok(())
   }
+
+  fn solution(&self) -> Option<Solution<S, usize>> {
+    let actions = self.last.actions();
+    let cost = actions.len();
+    Some(Solution::new(self.start.clone(), actions, cost).with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
 }
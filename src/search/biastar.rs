@@ -0,0 +1,282 @@
+use super::{tree::SearchTree, Search, SearchStats, Solution, State};
+use crate::{
+  state::Predecessors,
+  value::{SearchCost, SearchHeuristic},
+};
+use priority_queue::PriorityQueue;
+use std::{cmp::Reverse, collections::VecDeque, hash::Hash};
+
+/// One direction of a bidirectional A* search.
+struct Frontier<S: State, C: Ord>
+where
+  S: Hash + Eq,
+{
+  states: PriorityQueue<S, Reverse<C>>,
+  tree: SearchTree<S, C>,
+}
+
+/// An A* traversal from both the start and the goal state.
+///
+/// Each direction is an A* search towards the opposite end, so this takes a
+/// heuristic for the cost to the goal (as used by `Astar`) and another for the
+/// cost back to the start. The direction with the fewest states is expanded.
+///
+/// The search stops once the cheapest path found through a state reached by
+/// both directions is no more than the smallest f-value in either direction.
+/// With admissible heuristics, no cheaper path can then exist, so the path
+/// returned by `find_path` (or `solve`) is optimal.
+pub struct BidirectionalAstar<S: Predecessors, C, H, R>
+where
+  S: Hash + Eq,
+  C: SearchCost<S>,
+  H: SearchHeuristic<S, Cost = C::Cost>,
+  R: SearchHeuristic<S, Cost = C::Cost>,
+{
+  forward: Frontier<S, C::Cost>,
+  backward: Frontier<S, C::Cost>,
+  errors: VecDeque<S::Error>,
+  start: S,
+  goal: S,
+  action_cost: C,
+  heuristic: H,
+  reverse_heuristic: R,
+  meeting: Option<(C::Cost, usize, usize)>,
+  finished: bool,
+  stats: SearchStats,
+}
+
+impl<S: Predecessors, C, H, R> BidirectionalAstar<S, C, H, R>
+where
+  S: Hash + Eq + Clone,
+  C: SearchCost<S>,
+  H: SearchHeuristic<S, Cost = C::Cost>,
+  R: SearchHeuristic<S, Cost = C::Cost>,
+{
+  /// Creates a new search between the given start and goal states.
+  ///
+  /// `heuristic` should estimate the cost from a state to the goal, and
+  /// `reverse_heuristic` the cost from the start to a state.
+  pub fn new(start: S, goal: S, action_cost: C, heuristic: H, reverse_heuristic: R) -> Self {
+    let mut search = Self {
+      forward: Frontier {
+        states: PriorityQueue::new(),
+        tree: SearchTree::new(start.clone(), Default::default()),
+      },
+      backward: Frontier {
+        states: PriorityQueue::new(),
+        tree: SearchTree::new(goal.clone(), Default::default()),
+      },
+      errors: VecDeque::new(),
+      start: start.clone(),
+      goal,
+      action_cost,
+      heuristic,
+      reverse_heuristic,
+      meeting: None,
+      finished: false,
+      stats: SearchStats::default(),
+    };
+    search.reset(start);
+    search
+  }
+
+  /// Runs the search until the two directions meet, returning the actions
+  /// that lead from the start to the goal and their total cost.
+  pub fn find_path(&mut self) -> Option<(Vec<S::Action>, C::Cost)>
+  where
+    S::Action: Clone,
+  {
+    self.by_ref().for_each(drop);
+    self.path()
+  }
+
+  /// The actions along the cheapest path found so far, and their total cost
+  fn path(&self) -> Option<(Vec<S::Action>, C::Cost)>
+  where
+    S::Action: Clone,
+  {
+    let (cost, forward, backward) = self.meeting.clone()?;
+    let mut actions = self.forward.tree.actions(forward);
+    actions.extend(self.backward.tree.actions(backward).into_iter().rev());
+    Some((actions, cost))
+  }
+
+  fn reset(&mut self, start: S) {
+    let goal = self.goal.clone();
+    self.start = start.clone();
+    self.errors.clear();
+    self.meeting = (start == goal).then(|| (Default::default(), 0, 0));
+    self.finished = false;
+    self.stats = SearchStats::default();
+
+    self.forward.states.clear();
+    self.forward.tree.reset(start.clone(), Default::default());
+    match start.observe() {
+      Ok(obs) => {
+        let value = self.heuristic.value(&obs);
+        self.forward.states.push(start, Reverse(value));
+      }
+      Err(e) => self.errors.push_back(e.into()),
+    }
+
+    self.backward.states.clear();
+    self.backward.tree.reset(goal.clone(), Default::default());
+    match goal.observe() {
+      Ok(obs) => {
+        let value = self.reverse_heuristic.value(&obs);
+        self.backward.states.push(goal, Reverse(value));
+      }
+      Err(e) => self.errors.push_back(e.into()),
+    }
+  }
+}
+
+impl<S: Predecessors, C, H, R> Iterator for BidirectionalAstar<S, C, H, R>
+where
+  S: Hash + Eq + Clone,
+  C: SearchCost<S>,
+  H: SearchHeuristic<S, Cost = C::Cost>,
+  R: SearchHeuristic<S, Cost = C::Cost>,
+{
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if let Some(e) = self.errors.pop_front() {
+      return Some(Err(e));
+    }
+    if self.finished {
+      return None;
+    }
+
+    // Either direction running out means every path has been considered
+    let (Some((_, Reverse(forward_min))), Some((_, Reverse(backward_min)))) =
+      (self.forward.states.peek(), self.backward.states.peek())
+    else {
+      self.finished = true;
+      return None;
+    };
+    let f_min = forward_min.max(backward_min);
+    if self
+      .meeting
+      .as_ref()
+      .is_some_and(|(cost, _, _)| cost <= f_min)
+    {
+      self.finished = true;
+      return None;
+    }
+
+    let backwards = self.backward.states.len() < self.forward.states.len();
+    let (frontier, other) = if backwards {
+      (&mut self.backward, &self.forward)
+    } else {
+      (&mut self.forward, &self.backward)
+    };
+    let (state, _) = frontier.states.pop()?;
+
+    let id = frontier.tree.id(&state)?;
+    let path_cost = frontier.tree.node(id).cost.clone();
+    self.stats.expand(frontier.tree.node(id).depth);
+    let actions: Vec<_> = if backwards {
+      let actions = state.predecessors().into_iter();
+      actions
+        .map(|action| (state.reverse(&action), action))
+        .collect()
+    } else {
+      let actions = state.actions().into_iter();
+      actions
+        .map(|action| (state.result(&action), action))
+        .collect()
+    };
+
+    for (result, action) in actions {
+      self.stats.generate();
+      let new_state = match result {
+        Ok(new_state) => new_state,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      let new_cost = path_cost.clone() + self.action_cost.cost(&action);
+      let known = frontier.tree.id(&new_state);
+      if known.is_some_and(|id| frontier.tree.node(id).cost <= new_cost) {
+        self.stats.duplicate();
+        continue;
+      }
+
+      let value = match new_state.observe() {
+        Ok(obs) if backwards => self.reverse_heuristic.value(&obs),
+        Ok(obs) => self.heuristic.value(&obs),
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      let new_id = frontier
+        .tree
+        .insert(new_state.clone(), Some((id, action)), new_cost.clone());
+      if let Some(other_id) = other.tree.id(&new_state) {
+        let cost = new_cost.clone() + other.tree.node(other_id).cost.clone();
+        if self
+          .meeting
+          .as_ref()
+          .is_none_or(|(best, _, _)| &cost < best)
+        {
+          self.meeting = Some(if backwards {
+            (cost, other_id, new_id)
+          } else {
+            (cost, new_id, other_id)
+          });
+        }
+      }
+      frontier.states.push(new_state, Reverse(new_cost + value));
+    }
+
+    let stored = self.forward.states.len() + self.backward.states.len();
+    self.stats.frontier(stored);
+    Some(Ok(state))
+  }
+}
+
+impl<S: Predecessors, C, H, R> Search<S> for BidirectionalAstar<S, C, H, R>
+where
+  S: Hash + Eq + Clone,
+  C: SearchCost<S>,
+  H: SearchHeuristic<S, Cost = C::Cost>,
+  R: SearchHeuristic<S, Cost = C::Cost>,
+  S::Action: Clone,
+{
+  type Cost = C::Cost;
+
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    self.reset(start);
+    Ok(())
+  }
+
+  /// The cheapest path from the start to the goal, once the directions have
+  /// met. Unlike other searches, this isn't the path to the last state yielded.
+  fn solution(&self) -> Option<Solution<S, C::Cost>> {
+    let (actions, cost) = self.path()?;
+    let solution = Solution::new(self.start.clone(), actions, cost);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  /// Runs the search until the directions meet, then returns the path if the
+  /// goal state this search was created with satisfies `goal`.
+  ///
+  /// Stops at the first error, as with `Search::next_goal`.
+  fn solve(
+    &mut self,
+    goal: impl Fn(&S::Observation) -> bool,
+  ) -> Result<Option<Solution<S, C::Cost>>, S::Error> {
+    for state in self.by_ref() {
+      state?;
+    }
+    let obs = self.goal.observe()?;
+    Ok(goal(&obs).then(|| self.solution()).flatten())
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
+}
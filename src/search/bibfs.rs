@@ -0,0 +1,224 @@
+use super::{tree::SearchTree, Search, SearchStats, Solution, State};
+use crate::state::Predecessors;
+use std::{collections::VecDeque, hash::Hash};
+
+/// One direction of a bidirectional breadth first search.
+struct Frontier<S: State> {
+  states: VecDeque<Result<S, S::Error>>,
+  tree: SearchTree<S, usize>,
+}
+
+impl<S: State> Frontier<S>
+where
+  S: Hash + Eq + Clone,
+{
+  fn new(root: S) -> Self {
+    Self {
+      states: VecDeque::from([Ok(root.clone())]),
+      tree: SearchTree::new(root, 0),
+    }
+  }
+}
+
+/// A Breadth first traversal from both the start and the goal state.
+///
+/// Alternates between expanding a layer of states forwards from the start and
+/// a layer backwards from the goal, until the two searches meet. As each
+/// search only has to reach half way, this will explore far fewer states than
+/// `Bfs` when the goal state is known and actions can be reversed.
+///
+/// States from both directions are yielded as they are expanded, so to get the
+/// actions from the start to the goal, use `find_path` or `solve`.
+pub struct BidirectionalBfs<S: Predecessors>
+where
+  S: Hash + Eq,
+{
+  forward: Frontier<S>,
+  backward: Frontier<S>,
+  start: S,
+  goal: S,
+  backwards: bool,
+  layer: usize,
+  meeting: Option<(usize, usize, usize)>,
+  finished: bool,
+  stats: SearchStats,
+}
+
+impl<S: Predecessors> BidirectionalBfs<S>
+where
+  S: Hash + Eq + Clone,
+{
+  /// Creates a new search between the given start and goal states
+  pub fn new(start: S, goal: S) -> Self {
+    let meeting = (start == goal).then_some((0, 0, 0));
+    Self {
+      forward: Frontier::new(start.clone()),
+      backward: Frontier::new(goal.clone()),
+      start,
+      goal,
+      backwards: false,
+      layer: 0,
+      meeting,
+      finished: false,
+      stats: SearchStats::default(),
+    }
+  }
+
+  /// Runs the search until the two directions meet, returning the actions
+  /// that lead from the start to the goal.
+  ///
+  /// As every state in the last layer is checked for a meeting point, this
+  /// will always return a path with the fewest possible actions.
+  pub fn find_path(&mut self) -> Option<Vec<S::Action>>
+  where
+    S::Action: Clone,
+  {
+    self.by_ref().for_each(drop);
+    self.path().map(|(actions, _)| actions)
+  }
+
+  /// The actions along the shortest path found so far, and their number
+  fn path(&self) -> Option<(Vec<S::Action>, usize)>
+  where
+    S::Action: Clone,
+  {
+    let (length, forward, backward) = self.meeting?;
+    let mut actions = self.forward.tree.actions(forward);
+    actions.extend(self.backward.tree.actions(backward).into_iter().rev());
+    Some((actions, length))
+  }
+
+  /// Picks the direction with the smallest frontier to expand a layer of.
+  ///
+  /// Returns `false` if the search is finished, i.e. the directions have met
+  /// or either direction has run out of states.
+  fn next_layer(&mut self) -> bool {
+    if self.meeting.is_some() || self.forward.states.is_empty() || self.backward.states.is_empty() {
+      return false;
+    }
+    self.backwards = self.backward.states.len() < self.forward.states.len();
+    self.layer = if self.backwards {
+      self.backward.states.len()
+    } else {
+      self.forward.states.len()
+    };
+    true
+  }
+}
+
+impl<S: Predecessors> Iterator for BidirectionalBfs<S>
+where
+  S: Hash + Eq + Clone,
+{
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.finished {
+      return None;
+    }
+    if self.layer == 0 && !self.next_layer() {
+      self.finished = true;
+      return None;
+    }
+    self.layer -= 1;
+
+    let (frontier, other) = if self.backwards {
+      (&mut self.backward, &self.forward)
+    } else {
+      (&mut self.forward, &self.backward)
+    };
+    let state = match frontier.states.pop_front()? {
+      Ok(state) => state,
+      Err(e) => return Some(Err(e)),
+    };
+
+    let id = frontier.tree.id(&state)?;
+    self.stats.expand(frontier.tree.node(id).depth);
+    let depth = frontier.tree.node(id).cost + 1;
+    let actions: Vec<_> = if self.backwards {
+      let actions = state.predecessors().into_iter();
+      actions
+        .map(|action| (state.reverse(&action), action))
+        .collect()
+    } else {
+      let actions = state.actions().into_iter();
+      actions
+        .map(|action| (state.result(&action), action))
+        .collect()
+    };
+
+    for (result, action) in actions {
+      self.stats.generate();
+      let new_state = match result {
+        Ok(new_state) => new_state,
+        Err(e) => {
+          frontier.states.push_back(Err(e.into()));
+          continue;
+        }
+      };
+      if frontier.tree.id(&new_state).is_some() {
+        self.stats.duplicate();
+        continue;
+      }
+
+      let new_id = frontier
+        .tree
+        .insert(new_state.clone(), Some((id, action)), depth);
+      if let Some(other_id) = other.tree.id(&new_state) {
+        let length = depth + other.tree.node(other_id).cost;
+        if self.meeting.is_none_or(|(best, _, _)| length < best) {
+          self.meeting = Some(if self.backwards {
+            (length, other_id, new_id)
+          } else {
+            (length, new_id, other_id)
+          });
+        }
+      }
+      frontier.states.push_back(Ok(new_state));
+    }
+
+    let stored = self.forward.states.len() + self.backward.states.len();
+    self.stats.frontier(stored);
+    Some(Ok(state))
+  }
+}
+
+impl<S: Predecessors> Search<S> for BidirectionalBfs<S>
+where
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
+{
+  type Cost = usize;
+
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    *self = Self::new(start, self.goal.clone());
+    Ok(())
+  }
+
+  /// The shortest path from the start to the goal, once the directions have
+  /// met. Unlike other searches, this isn't the path to the last state yielded.
+  fn solution(&self) -> Option<Solution<S, usize>> {
+    let (actions, length) = self.path()?;
+    let solution = Solution::new(self.start.clone(), actions, length);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  /// Runs the search until the directions meet, then returns the path if the
+  /// goal state this search was created with satisfies `goal`.
+  ///
+  /// Stops at the first error, as with `Search::next_goal`.
+  fn solve(
+    &mut self,
+    goal: impl Fn(&S::Observation) -> bool,
+  ) -> Result<Option<Solution<S, usize>>, S::Error> {
+    for state in self.by_ref() {
+      state?;
+    }
+    let obs = self.goal.observe()?;
+    Ok(goal(&obs).then(|| self.solution()).flatten())
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
+}
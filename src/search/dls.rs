@@ -1,30 +1,60 @@
-use super::{Search, State};
-use crate::wrappers::{DepthState, StateWrapper};
+use super::{
+  trail::{Trail, TrailState},
+  Search, SearchStats, Solution, State,
+};
+use crate::wrappers::StateWrapper;
 
 /// A Depth first traversal of possible states, up to a given depth.
 ///
 /// As we give a limit to the depth of the search, we can avoid infinite loops.
 pub struct Dls<S: State> {
-  states: Vec<Result<DepthState<S>, S::Error>>,
+  states: Vec<Result<TrailState<S>, S::Error>>,
+  start: S,
+  last: Trail<S::Action>,
   limit: usize,
+  cut_off: bool,
+  stats: SearchStats,
 }
 
-impl<S: State> Dls<S> {
+impl<S: State> Dls<S>
+where
+  S: Clone,
+{
   pub fn new(start: S, limit: usize) -> Self {
     Self {
-      states: vec![Ok(start.into())],
+      states: vec![Ok(start.clone().into())],
+      start,
+      last: Trail::default(),
       limit,
+      cut_off: false,
+      stats: SearchStats::default(),
     }
   }
 
+  /// Whether any state at the depth limit has been reached that has actions,
+  /// i.e. a deeper limit would explore more states.
+  pub fn is_cut_off(&self) -> bool {
+    self.cut_off
+  }
+
+  /// Restarts the search from the given state with a deeper limit.
+  ///
+  /// Unlike `restart_from`, this keeps the stats from the shallower searches.
   pub fn increment_limit(&mut self, start: S) -> Result<(), S::Error> {
     self.limit += 1;
-    self.restart_from(start)?;
+    self.cut_off = false;
+    self.states.clear();
+    self.states.push(Ok(start.clone().into()));
+    self.start = start;
+    self.last = Trail::default();
     Ok(())
   }
 }
 
-impl<S: State> Iterator for Dls<S> {
+impl<S: State> Iterator for Dls<S>
+where
+  S::Action: Clone,
+{
   type Item = Result<S, S::Error>;
 
   fn next(&mut self) -> Option<Self::Item> {
@@ -33,25 +63,50 @@ impl<S: State> Iterator for Dls<S> {
       Ok(state) => state,
       Err(e) => return Some(Err(e)),
     };
+    self.last = state.trail().clone();
 
     if state.depth() >= self.limit {
+      self.cut_off |= state.actions().into_iter().next().is_some();
       return Some(Ok(state.unwrap()));
     }
 
-    let actions = state
-      .actions()
-      .into_iter()
-      .map(|action| state.result(&action).map_err(S::Error::from));
+    self.stats.expand(state.depth());
+    let actions = state.actions().into_iter().map(|action| {
+      let result = state.result(&action).map_err(S::Error::from);
+      self.stats.generate();
+      result
+    });
 
     self.states.extend(actions);
+    self.stats.frontier(self.states.len());
     Some(Ok(state.unwrap()))
   }
 }
 
-impl<S: State> Search<S> for Dls<S> {
+impl<S: State> Search<S> for Dls<S>
+where
+  S: Clone,
+  S::Action: Clone,
+{
+  type Cost = usize;
+
   fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    self.cut_off = false;
     self.states.clear();
-    self.states.push(Ok(start.into()));
+    self.states.push(Ok(start.clone().into()));
+    self.start = start;
+    self.last = Trail::default();
+    self.stats = SearchStats::default();
     Ok(())
   }
+
+  fn solution(&self) -> Option<Solution<S, usize>> {
+    let actions = self.last.actions();
+    let cost = actions.len();
+    Some(Solution::new(self.start.clone(), actions, cost).with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
 }
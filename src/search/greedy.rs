@@ -1,7 +1,10 @@
-use super::{Search, State};
-use crate::value::SearchHeuristic;
+use super::{
+  trail::{Trail, TrailState},
+  Search, SearchStats, Solution, State,
+};
+use crate::{value::SearchHeuristic, wrappers::StateWrapper};
 use priority_queue::PriorityQueue;
-use std::{cmp::Reverse, hash::Hash};
+use std::{cmp::Reverse, collections::VecDeque, hash::Hash};
 
 /// A Greedy traversal of possible states.
 ///
@@ -10,52 +13,121 @@ pub struct Greedy<S: State, H: SearchHeuristic<S>>
 where
   S: Hash + Eq,
 {
-  states: PriorityQueue<S, Reverse<H::Cost>>,
+  states: PriorityQueue<TrailState<S>, Reverse<H::Cost>>,
+  errors: VecDeque<S::Error>,
+  start: S,
+  last: Trail<S::Action>,
   heuristic: H,
+  stats: SearchStats,
 }
 
 impl<S: State, H: SearchHeuristic<S>> Greedy<S, H>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
 {
+  /// Creates a new search from the given start state.
+  ///
+  /// If the start state can't be observed, the error is yielded by the search.
   pub fn new(start: S, heuristic: H) -> Self {
-    let mut states = PriorityQueue::new();
-    let obs = start.observe().map_err(S::Error::from).unwrap();
-    let cost = heuristic.value(&obs);
-    states.push(start, Reverse(cost));
-    Self { states, heuristic }
+    let mut search = Self::empty(start.clone(), heuristic);
+    if let Err(e) = search.reset(start) {
+      search.errors.push_back(e);
+    }
+    search
+  }
+
+  /// Creates a new search from the given start state, failing if the start
+  /// state can't be observed.
+  pub fn try_new(start: S, heuristic: H) -> Result<Self, S::Error> {
+    let mut search = Self::empty(start.clone(), heuristic);
+    search.reset(start)?;
+    Ok(search)
+  }
+
+  fn empty(start: S, heuristic: H) -> Self {
+    Self {
+      states: PriorityQueue::new(),
+      errors: VecDeque::new(),
+      start,
+      last: Trail::default(),
+      heuristic,
+      stats: SearchStats::default(),
+    }
+  }
+
+  fn reset(&mut self, start: S) -> Result<(), S::Error> {
+    self.states.clear();
+    self.errors.clear();
+    self.last = Trail::default();
+    self.stats = SearchStats::default();
+    self.start = start.clone();
+    let cost = self.heuristic.value(&start.observe()?);
+    self.states.push(start.into(), Reverse(cost));
+    Ok(())
   }
 }
 
 impl<S, H> Iterator for Greedy<S, H>
 where
   S: State + Hash + Eq,
+  S::Action: Clone,
   H: SearchHeuristic<S>,
 {
   type Item = Result<S, S::Error>;
 
   fn next(&mut self) -> Option<Self::Item> {
+    if let Some(e) = self.errors.pop_front() {
+      return Some(Err(e));
+    }
     let (state, _) = self.states.pop()?;
 
-    let actions = state.actions().into_iter().filter_map(|action| {
-      let new_state = state.result(&action).ok()?;
-      let value = self.heuristic.value(&new_state.observe().ok()?);
-      Some((new_state, Reverse(value)))
-    });
+    self.stats.expand(state.depth());
+    for action in state.actions() {
+      self.stats.generate();
+      let new_state = match state.result(&action) {
+        Ok(new_state) => new_state,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      let obs = match new_state.observe() {
+        Ok(obs) => obs,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      let value = self.heuristic.value(&obs);
+      if self.states.push(new_state, Reverse(value)).is_some() {
+        self.stats.duplicate();
+      }
+    }
 
-    self.states.extend(actions);
-    Some(Ok(state))
+    self.stats.frontier(self.states.len());
+    self.last = state.trail().clone();
+    Some(Ok(state.unwrap()))
   }
 }
 
 impl<S: State, C: SearchHeuristic<S>> Search<S> for Greedy<S, C>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
 {
+  type Cost = usize;
+
   fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
-    self.states.clear();
-    let cost = self.heuristic.value(&start.observe()?);
-    self.states.push(start, Reverse(cost));
-    Ok(())
+    self.reset(start)
+  }
+
+  fn solution(&self) -> Option<Solution<S, usize>> {
+    let actions = self.last.actions();
+    let cost = actions.len();
+    Some(Solution::new(self.start.clone(), actions, cost).with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
   }
 }
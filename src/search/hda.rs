@@ -0,0 +1,350 @@
+use super::{helpers::FValue, trail::Trail, SearchStats, Solution, State};
+use crate::value::{SearchCost, SearchHeuristic};
+use priority_queue::PriorityQueue;
+use std::{
+  cmp::Reverse,
+  collections::{hash_map::RandomState, HashMap},
+  hash::{BuildHasher, Hash},
+  marker::PhantomData,
+  ops::Add,
+  sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    Mutex, RwLock,
+  },
+  thread,
+  time::Duration,
+};
+
+/// How long an idle thread waits for a message before checking whether the
+/// search has finished.
+const IDLE_WAIT: Duration = Duration::from_millis(1);
+
+/// A state sent to the thread that owns it, with the path taken to reach it.
+struct Node<S: State, G> {
+  state: S,
+  cost: G,
+  trail: Trail<S::Action>,
+}
+
+/// The best goal found so far, as its f-value, path and path cost.
+type Incumbent<S, C, H> = (
+  FValue<S, C, H>,
+  Trail<<S as State>::Action>,
+  <C as SearchCost<S>>::Cost,
+);
+
+/// The state shared between the threads of a search.
+struct Shared<S: State, C: SearchCost<S>, H: SearchHeuristic<S>>
+where
+  C::Cost: Add<H::Cost>,
+{
+  /// The number of threads that are working plus the number of messages that
+  /// have been sent but not yet handled. The search is over once this is 0.
+  work: AtomicUsize,
+  /// Set when a thread fails, to stop the others
+  stop: AtomicBool,
+  best: RwLock<Option<Incumbent<S, C, H>>>,
+  /// The number of times a cheaper goal has been found, so that threads can
+  /// tell when their copy of its f-value is out of date
+  improvements: AtomicUsize,
+  error: Mutex<Option<S::Error>>,
+}
+
+/// Stops every thread of a search if the thread holding it panics, so the
+/// others don't wait forever for work it will never send.
+struct StopOnPanic<'a>(&'a AtomicBool);
+
+impl Drop for StopOnPanic<'_> {
+  fn drop(&mut self) {
+    if thread::panicking() {
+      self.0.store(true, Ordering::SeqCst);
+    }
+  }
+}
+
+/// The part of the search run by a single thread.
+struct Worker<'a, S: State, C: SearchCost<S>, H: SearchHeuristic<S>, G>
+where
+  S: Hash + Eq,
+  C::Cost: Add<H::Cost>,
+  FValue<S, C, H>: Ord,
+{
+  id: usize,
+  action_cost: &'a C,
+  heuristic: &'a H,
+  shared: &'a Shared<S, C, H>,
+  hasher: &'a RandomState,
+  outboxes: Vec<Sender<Node<S, C::Cost>>>,
+  goal: &'a G,
+  states: PriorityQueue<S, Reverse<FValue<S, C, H>>>,
+  costs: HashMap<S, (C::Cost, Trail<S::Action>)>,
+  bound: (usize, Option<FValue<S, C, H>>),
+  stats: SearchStats,
+}
+
+/// A hash distributed A* (HDA*) search, which runs A* on many threads at once.
+///
+/// Each thread owns the states whose hash falls in its partition, and keeps
+/// the frontier and best known path costs for those states only. Successors
+/// are sent to the thread that owns them, so every state is only ever expanded
+/// by one thread, and the threads only share the best goal found so far.
+///
+/// Goals are checked as states are reached, and the cheapest is kept until
+/// every thread has run out of states that could lead to a cheaper one. Like
+/// `Astar`, the solution is optimal for any admissible heuristic.
+///
+/// To tell when the search is over, a single counter holds the number of
+/// threads still working plus the number of states sent but not yet received.
+/// A thread that runs out of work only decrements it after handling all of
+/// its messages, and a thread woken up by a message increments it before
+/// marking the message as handled, so the counter can only reach 0 once there
+/// is no work left anywhere.
+pub struct Hda<S: State, C, H> {
+  start: S,
+  action_cost: C,
+  heuristic: H,
+  threads: usize,
+  stats: SearchStats,
+  _state: PhantomData<S>,
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Hda<S, C, H> {
+  /// Creates a new search from the given start state, using every available
+  /// core.
+  pub fn new(start: S, action_cost: C, heuristic: H) -> Self {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    Self {
+      start,
+      action_cost,
+      heuristic,
+      threads,
+      stats: SearchStats::default(),
+      _state: PhantomData,
+    }
+  }
+
+  /// Sets the number of threads to search with (at least 1).
+  pub fn threads(mut self, threads: usize) -> Self {
+    self.threads = threads.max(1);
+    self
+  }
+
+  /// Statistics on the work done by the last search, summed over every
+  /// thread.
+  pub fn stats(&self) -> &SearchStats {
+    &self.stats
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Hda<S, C, H>
+where
+  S: Hash + Eq + Clone + Send,
+  S::Action: Clone + Send + Sync,
+  S::Error: Send,
+  C: Sync,
+  C::Cost: Add<H::Cost> + Send + Sync,
+  FValue<S, C, H>: Ord + Clone + Send + Sync,
+  H: Sync,
+{
+  /// Searches for the cheapest path to a goal, returning `None` if no goal can
+  /// be reached.
+  ///
+  /// If any thread fails to expand a state, the search is stopped and the
+  /// error returned. If any thread panics, the search is stopped and the panic
+  /// resumed on this thread.
+  pub fn solve(
+    &mut self,
+    goal: impl Fn(&S::Observation) -> bool + Sync,
+  ) -> Result<Option<Solution<S, C::Cost>>, S::Error> {
+    let shared = Shared {
+      work: AtomicUsize::new(self.threads + 1),
+      stop: AtomicBool::new(false),
+      best: RwLock::new(None),
+      improvements: AtomicUsize::new(0),
+      error: Mutex::new(None),
+    };
+    let hasher = RandomState::new();
+    let (outboxes, inboxes): (Vec<_>, Vec<_>) = (0..self.threads).map(|_| mpsc::channel()).unzip();
+    let owner = (hasher.hash_one(&self.start) % self.threads as u64) as usize;
+    let start = Node {
+      state: self.start.clone(),
+      cost: C::Cost::default(),
+      trail: Trail::default(),
+    };
+    let _ = outboxes[owner].send(start);
+
+    let stats = thread::scope(|scope| {
+      let workers = (inboxes.into_iter().enumerate())
+        .map(|(id, inbox)| {
+          let mut worker = Worker::<S, C, H, _> {
+            id,
+            action_cost: &self.action_cost,
+            heuristic: &self.heuristic,
+            shared: &shared,
+            hasher: &hasher,
+            outboxes: outboxes.clone(),
+            goal: &goal,
+            states: PriorityQueue::new(),
+            costs: HashMap::new(),
+            bound: (0, None),
+            stats: SearchStats::default(),
+          };
+          let stop = &shared.stop;
+          scope.spawn(move || {
+            let _guard = StopOnPanic(stop);
+            worker.run(inbox);
+            worker.stats
+          })
+        })
+        .collect::<Vec<_>>();
+      let mut stats = SearchStats::default();
+      for worker in workers {
+        match worker.join() {
+          Ok(worker) => stats.merge(&worker),
+          Err(panic) => std::panic::resume_unwind(panic),
+        }
+      }
+      stats
+    });
+    self.stats = stats;
+
+    if let Some(e) = shared.error.into_inner().unwrap() {
+      return Err(e);
+    }
+    let solution = shared.best.into_inner().unwrap().map(|(_, trail, cost)| {
+      let solution = Solution::new(self.start.clone(), trail.actions(), cost);
+      solution.with_stats(Some(&self.stats))
+    });
+    Ok(solution)
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>, G> Worker<'_, S, C, H, G>
+where
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
+  C::Cost: Add<H::Cost>,
+  FValue<S, C, H>: Ord + Clone,
+  G: Fn(&S::Observation) -> bool,
+{
+  /// Expands states until there is no work left on any thread.
+  fn run(&mut self, inbox: Receiver<Node<S, C::Cost>>) {
+    let shared = self.shared;
+    let work = &shared.work;
+    while !shared.stop.load(Ordering::SeqCst) {
+      while let Ok(node) = inbox.try_recv() {
+        self.receive(node);
+        work.fetch_sub(1, Ordering::SeqCst);
+      }
+      if self.expand() {
+        continue;
+      }
+
+      // Out of work until another thread sends some
+      work.fetch_sub(1, Ordering::SeqCst);
+      loop {
+        if shared.stop.load(Ordering::SeqCst) {
+          return;
+        }
+        match inbox.recv_timeout(IDLE_WAIT) {
+          Ok(node) => {
+            work.fetch_add(1, Ordering::SeqCst);
+            self.receive(node);
+            work.fetch_sub(1, Ordering::SeqCst);
+            break;
+          }
+          Err(RecvTimeoutError::Timeout) if work.load(Ordering::SeqCst) > 0 => {}
+          Err(_) => return,
+        }
+      }
+    }
+  }
+
+  /// Whether a state with the given f-value can't lead to a cheaper goal than
+  /// the best found so far.
+  fn pruned(&mut self, f: &FValue<S, C, H>) -> bool {
+    let improvements = self.shared.improvements.load(Ordering::SeqCst);
+    if improvements != self.bound.0 {
+      let best = self.shared.best.read().unwrap();
+      self.bound = (improvements, best.as_ref().map(|(best, ..)| best.clone()));
+    }
+    (self.bound.1.as_ref()).is_some_and(|best| best <= f)
+  }
+
+  /// Records the first error, and stops every thread.
+  fn fail(&self, e: S::Error) {
+    self.shared.error.lock().unwrap().get_or_insert(e);
+    self.shared.stop.store(true, Ordering::SeqCst);
+  }
+
+  /// Adds a state owned by this thread to the frontier, unless a path at
+  /// least as cheap is already known.
+  fn receive(&mut self, Node { state, cost, trail }: Node<S, C::Cost>) {
+    if (self.costs.get(&state)).is_some_and(|(known, _)| known <= &cost) {
+      self.stats.duplicate();
+      return;
+    }
+    let obs = match state.observe() {
+      Ok(obs) => obs,
+      Err(e) => return self.fail(e.into()),
+    };
+    let value = self.heuristic.value(&obs);
+    let f = <C::Cost as Add<H::Cost>>::add(cost.clone(), value);
+    self
+      .costs
+      .insert(state.clone(), (cost.clone(), trail.clone()));
+    if self.pruned(&f) {
+      return;
+    }
+    if (self.goal)(&obs) {
+      let mut best = self.shared.best.write().unwrap();
+      if best.as_ref().is_none_or(|(best, ..)| &f < best) {
+        *best = Some((f, trail, cost));
+        self.shared.improvements.fetch_add(1, Ordering::SeqCst);
+      }
+      return;
+    }
+    self.states.push(state, Reverse(f));
+    self.stats.frontier(self.states.len());
+  }
+
+  /// Expands the state with the lowest f-value, sending its successors to the
+  /// threads that own them. Returns `false` if there is nothing worth
+  /// expanding.
+  fn expand(&mut self) -> bool {
+    let Some((state, Reverse(f))) = self.states.pop() else {
+      return false;
+    };
+    if self.pruned(&f) {
+      // Every other state in the frontier is at least as expensive
+      self.states.clear();
+      return false;
+    }
+    let (cost, trail) = self.costs[&state].clone();
+
+    self.stats.expand(trail.depth());
+    for action in state.actions() {
+      self.stats.generate();
+      let node = match state.result(&action) {
+        Ok(new_state) => Node {
+          state: new_state,
+          cost: cost.clone() + self.action_cost.cost(&action),
+          trail: trail.push(action),
+        },
+        Err(e) => {
+          self.fail(e.into());
+          return false;
+        }
+      };
+      let owner = (self.hasher.hash_one(&node.state) % self.outboxes.len() as u64) as usize;
+      if owner == self.id {
+        self.receive(node);
+        continue;
+      }
+      self.shared.work.fetch_add(1, Ordering::SeqCst);
+      let _ = self.outboxes[owner].send(node);
+    }
+    true
+  }
+}
@@ -0,0 +1,17 @@
+use crate::value::{SearchCost, SearchHeuristic};
+use std::ops::Add;
+
+/// An estimate of a path cost that may be infinite.
+///
+/// This is used by the memory bounded searches to mark states that have no
+/// path to a goal (or none that fits in memory), and sorts after every finite
+/// estimate.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Estimate<F> {
+  Finite(F),
+  Infinite,
+}
+
+/// The f-value (path cost + heuristic) of a state in an A* style search
+pub(crate) type FValue<S, C, H> =
+  <<C as SearchCost<S>>::Cost as Add<<H as SearchHeuristic<S>>::Cost>>::Output;
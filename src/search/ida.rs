@@ -0,0 +1,165 @@
+use super::{
+  trail::{CostTrailState, Trail},
+  Search, SearchStats, Solution, State,
+};
+use crate::{
+  value::{SearchCost, SearchHeuristic},
+  wrappers::{PathCostState, StateWrapper},
+};
+use std::ops::Add;
+
+/// An Iterative deepening A* search.
+///
+/// This search will perform a depth first search, pruning any state whose
+/// f-value (path cost + heuristic) exceeds the current bound. It then restarts
+/// with the bound raised to the smallest f-value that was pruned. This will
+/// continue until a goal state is found or no states were pruned in a pass.
+///
+/// Like `Ids`, this only ever stores the states on the current path (and their
+/// siblings), so it can be used on problems where `Astar` runs out of memory.
+pub struct Ida<S: State, C: SearchCost<S>, H: SearchHeuristic<S>>
+where
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+{
+  states: Vec<Result<CostTrailState<S, C>, S::Error>>,
+  start: S,
+  last: (Trail<S::Action>, C::Cost),
+  action_cost: C,
+  heuristic: H,
+  bound: Option<<C::Cost as Add<H::Cost>>::Output>,
+  next_bound: Option<<C::Cost as Add<H::Cost>>::Output>,
+  stats: SearchStats,
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Ida<S, C, H>
+where
+  S: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  /// Creates a new search from the given start state
+  pub fn new(start: S, action_cost: C, heuristic: H) -> Self {
+    Self {
+      states: vec![Ok(
+        PathCostState::new(start.clone(), action_cost.clone()).into(),
+      )],
+      start,
+      last: Default::default(),
+      action_cost,
+      heuristic,
+      bound: None,
+      next_bound: None,
+      stats: SearchStats::default(),
+    }
+  }
+
+  /// Starts the next pass from the start state, with the bound raised to the
+  /// smallest f-value that exceeded it in the last pass.
+  ///
+  /// Returns `false` if no states were pruned, i.e. the last pass explored
+  /// every reachable state, in which case the search is left unchanged.
+  fn raise_bound(&mut self) -> bool {
+    let Some(bound) = self.next_bound.take() else {
+      return false;
+    };
+    self.states.clear();
+    let start = PathCostState::new(self.start.clone(), self.action_cost.clone());
+    self.states.push(Ok(start.into()));
+    self.bound = Some(bound);
+    true
+  }
+
+  fn f_value(
+    &self,
+    state: &PathCostState<S, C>,
+  ) -> Result<<C::Cost as Add<H::Cost>>::Output, S::Error> {
+    let value = self.heuristic.value(&state.observe()?);
+    Ok(<C::Cost as Add<H::Cost>>::add(state.path_cost(), value))
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Iterator for Ida<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let Some(result) = self.states.pop() else {
+        if self.raise_bound() {
+          continue;
+        }
+        return None;
+      };
+      let state = match result {
+        Ok(state) => state,
+        Err(e) => return Some(Err(e)),
+      };
+
+      let value = match self.f_value(state.state()) {
+        Ok(value) => value,
+        Err(e) => return Some(Err(e)),
+      };
+      // The first state of a search is the start, which sets the bound
+      let bound = self.bound.get_or_insert_with(|| value.clone());
+      if &value > bound {
+        if self.next_bound.as_ref().is_none_or(|next| &value < next) {
+          self.next_bound = Some(value);
+        }
+        continue;
+      }
+
+      self.stats.expand(state.depth());
+      let actions = state.actions().into_iter().map(|action| {
+        let result = state.result(&action).map_err(S::Error::from);
+        self.stats.generate();
+        result
+      });
+
+      self.states.extend(actions);
+      self.stats.frontier(self.states.len());
+      self.last = (state.trail().clone(), state.state().path_cost());
+      return Some(Ok(state.unwrap().unwrap()));
+    }
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Search<S> for Ida<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  type Cost = C::Cost;
+
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    self.states.clear();
+    let state = PathCostState::new(start.clone(), self.action_cost.clone());
+    self.states.push(Ok(state.into()));
+    self.start = start;
+    self.last = Default::default();
+    self.bound = None;
+    self.next_bound = None;
+    self.stats = SearchStats::default();
+    Ok(())
+  }
+
+  fn solution(&self) -> Option<Solution<S, C::Cost>> {
+    let (trail, cost) = self.last.clone();
+    let solution = Solution::new(self.start.clone(), trail.actions(), cost);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
+}
@@ -1,9 +1,10 @@
-use super::{Dls, Search, State};
+use super::{Dls, Search, SearchStats, Solution, State};
 
 /// An Iterative deepening search.
 ///
 /// This search will perform a depth first search up to a given depth, then
-/// restart with a deeper depth. This will continue until a goal state is found.
+/// restart with a deeper depth. This will continue until a goal state is found,
+/// or a pass reaches every reachable state without hitting the depth limit.
 pub struct Ids<S: State> {
   search: Dls<S>,
   start: S,
@@ -24,6 +25,7 @@ where
 impl<S: State> Iterator for Ids<S>
 where
   S: Clone,
+  S::Action: Clone,
 {
   type Item = Result<S, S::Error>;
 
@@ -33,6 +35,9 @@ where
       if result.is_some() {
         return result;
       }
+      if !self.search.is_cut_off() {
+        return None;
+      }
 
       let set_result = self.search.increment_limit(self.start.clone());
       if let Err(state_error) = set_result {
@@ -45,9 +50,21 @@ where
 impl<S: State> Search<S> for Ids<S>
 where
   S: Clone,
+  S::Action: Clone,
 {
+  type Cost = usize;
+
   fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
     self.search.restart_from(start.clone())?;
+    self.start = start;
     Ok(())
   }
+
+  fn solution(&self) -> Option<Solution<S, usize>> {
+    self.search.solution()
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    self.search.stats()
+  }
 }
@@ -0,0 +1,179 @@
+use super::{Search, SearchStats, Solution, State};
+use std::{
+  marker::PhantomData,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::{Duration, Instant},
+};
+
+/// The limit that stopped a search early.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+  /// The maximum number of expanded states was reached
+  Expansions,
+  /// The deadline passed
+  Deadline,
+  /// More states were stored than the memory budget allows
+  Memory,
+  /// The search was cancelled from elsewhere
+  Cancelled,
+}
+
+/// Limits on how much work a search may do before giving up.
+///
+/// By default, a search has no limits.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+  /// The maximum number of states to expand
+  pub max_expansions: Option<usize>,
+  /// The time by which the search should have finished
+  pub deadline: Option<Instant>,
+  /// The maximum number of states that can be stored at once
+  pub memory: Option<usize>,
+  /// A flag that stops the search once set
+  pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimits {
+  /// Creates a new set of limits, with no limits set
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Stops the search after the given number of states have been expanded
+  pub fn expansions(mut self, max_expansions: usize) -> Self {
+    self.max_expansions = Some(max_expansions);
+    self
+  }
+
+  /// Stops the search once the given time has passed
+  pub fn deadline(mut self, deadline: Instant) -> Self {
+    self.deadline = Some(deadline);
+    self
+  }
+
+  /// Stops the search once the given time has passed from now
+  pub fn timeout(self, timeout: Duration) -> Self {
+    self.deadline(Instant::now() + timeout)
+  }
+
+  /// Stops the search once it has stored more than the given number of states
+  pub fn memory(mut self, memory: usize) -> Self {
+    self.memory = Some(memory);
+    self
+  }
+
+  /// Stops the search once the given flag is set
+  pub fn cancel_with(mut self, cancel: Arc<AtomicBool>) -> Self {
+    self.cancel = Some(cancel);
+    self
+  }
+
+  /// Finds the first limit that has been reached, if any.
+  ///
+  /// States yielded are used in place of states expanded for searches that
+  /// don't collect stats, and the memory budget is only checked for those that
+  /// do.
+  pub fn reached(&self, stats: Option<&SearchStats>, yielded: usize) -> Option<Limit> {
+    if (self.cancel.as_ref()).is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+      return Some(Limit::Cancelled);
+    }
+    if self
+      .deadline
+      .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+      return Some(Limit::Deadline);
+    }
+    let expanded = stats.map_or(yielded, |stats| stats.expanded);
+    if self.max_expansions.is_some_and(|max| expanded >= max) {
+      return Some(Limit::Expansions);
+    }
+    let stored = stats.map(|stats| stats.peak_frontier);
+    if (self.memory.zip(stored)).is_some_and(|(memory, stored)| stored > memory) {
+      return Some(Limit::Memory);
+    }
+    None
+  }
+}
+
+/// A search that gives up once any of its `SearchLimits` is reached.
+///
+/// The limits are checked before each state is requested from the underlying
+/// search. Once a limit is reached, the search ends early and `reached` gives
+/// the limit responsible, so that giving up can be told apart from the search
+/// running out of states.
+pub struct Limited<S: State, Q: Search<S>> {
+  search: Q,
+  limits: SearchLimits,
+  yielded: usize,
+  reached: Option<Limit>,
+  _state: PhantomData<S>,
+}
+
+impl<S: State, Q: Search<S>> Limited<S, Q> {
+  /// Limits the given search
+  pub fn new(search: Q, limits: SearchLimits) -> Self {
+    Self {
+      search,
+      limits,
+      yielded: 0,
+      reached: None,
+      _state: PhantomData,
+    }
+  }
+
+  /// The limit that stopped the search, if it was stopped early
+  pub fn reached(&self) -> Option<Limit> {
+    self.reached
+  }
+
+  /// The limits placed on the search
+  pub fn limits(&self) -> &SearchLimits {
+    &self.limits
+  }
+
+  /// Removes the limits, returning the underlying search
+  pub fn into_inner(self) -> Q {
+    self.search
+  }
+}
+
+impl<S: State, Q: Search<S>> Iterator for Limited<S, Q> {
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.reached.is_some() {
+      return None;
+    }
+    self.reached = self.limits.reached(self.search.stats(), self.yielded);
+    if self.reached.is_some() {
+      return None;
+    }
+    let item = self.search.next()?;
+    self.yielded += 1;
+    Some(item)
+  }
+}
+
+impl<S: State, Q: Search<S>> Search<S> for Limited<S, Q> {
+  type Cost = Q::Cost;
+
+  /// Restarts the underlying search, keeping the same limits.
+  ///
+  /// As the deadline is a fixed point in time, it is not extended.
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    self.yielded = 0;
+    self.reached = None;
+    self.search.restart_from(start)
+  }
+
+  fn solution(&self) -> Option<Solution<S, Q::Cost>> {
+    self.search.solution()
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    self.search.stats()
+  }
+}
@@ -5,19 +5,48 @@
 
 use crate::state::State;
 
+mod ara;
 mod astar;
+mod beam;
 mod bfs;
+mod biastar;
+mod bibfs;
 mod dfs;
 mod dls;
 mod greedy;
+mod hda;
+mod helpers;
+mod ida;
 mod ids;
+mod limits;
+mod policy;
+mod portfolio;
+mod rbfs;
+mod sma;
+mod solution;
+mod stats;
+mod trail;
+mod tree;
 mod uniform;
+pub use ara::Ara;
 pub use astar::Astar;
+pub use beam::{Beam, BeamRank};
 pub use bfs::Bfs;
+pub use biastar::BidirectionalAstar;
+pub use bibfs::BidirectionalBfs;
 pub use dfs::Dfs;
 pub use dls::Dls;
 pub use greedy::Greedy;
+pub use hda::Hda;
+pub use ida::Ida;
 pub use ids::Ids;
+pub use limits::{Limit, Limited, SearchLimits};
+pub use policy::{ErrorPolicy, WithErrorPolicy};
+pub use portfolio::Portfolio;
+pub use rbfs::Rbfs;
+pub use sma::SmaStar;
+pub use solution::Solution;
+pub use stats::SearchStats;
 pub use uniform::Uniform;
 
 /// A generic search algorithm should act as an traversal over some tree of
@@ -26,18 +55,66 @@ pub use uniform::Uniform;
 /// Using the implemented traversal, we can provide implementations for the
 /// next valid and goal state.
 pub trait Search<S: State>: Iterator<Item = Result<S, S::Error>> + Sized {
+  /// The type of path cost given in solutions
+  type Cost;
+
   /// Restarts the search from the given state
   fn restart_from(&mut self, start: S) -> Result<(), S::Error>;
 
-  /// Returns the next valid state generated by this search
-  fn next_valid(&mut self) -> Option<S> {
-    self.find_map(|item| item.ok())
+  /// The path to the state most recently yielded by this search, if it keeps
+  /// track of how states were reached.
+  fn solution(&self) -> Option<Solution<S, Self::Cost>> {
+    None
+  }
+
+  /// Statistics on the work done since the search was started, if it
+  /// collects them.
+  fn stats(&self) -> Option<&SearchStats> {
+    None
+  }
+
+  /// Stops this search once any of the given limits is reached.
+  ///
+  /// See `Limited` for how to tell whether a limit was reached.
+  fn limit(self, limits: SearchLimits) -> Limited<S, Self> {
+    Limited::new(self, limits)
+  }
+
+  /// Handles errors from this search with the given policy.
+  ///
+  /// Without a policy, errors are yielded as `Err` items (as with
+  /// `ErrorPolicy::Propagate`).
+  fn on_error(self, policy: ErrorPolicy) -> WithErrorPolicy<S, Self> {
+    WithErrorPolicy::new(self, policy)
+  }
+
+  /// Returns the next state generated by this search, or the error if it
+  /// failed to generate one.
+  fn next_valid(&mut self) -> Result<Option<S>, S::Error> {
+    self.next().transpose()
   }
 
   /// Returns the next goal state generated by this search.
-  fn next_goal(&mut self, goal: impl Fn(&S::Observation) -> bool) -> Option<S> {
-    self
-      .filter_map(|item| item.ok())
-      .find(|state| state.observe().as_ref().map_or(false, &goal))
+  ///
+  /// Stops at the first error yielded by the search, or in observing a state,
+  /// so use `on_error` to skip errors instead.
+  fn next_goal(&mut self, goal: impl Fn(&S::Observation) -> bool) -> Result<Option<S>, S::Error> {
+    for state in self.by_ref() {
+      let state = state?;
+      if goal(&state.observe()?) {
+        return Ok(Some(state));
+      }
+    }
+    Ok(None)
+  }
+
+  /// Returns the path to the next goal state generated by this search.
+  ///
+  /// As with `next_goal`, stops at the first error.
+  fn solve(
+    &mut self,
+    goal: impl Fn(&S::Observation) -> bool,
+  ) -> Result<Option<Solution<S, Self::Cost>>, S::Error> {
+    Ok(self.next_goal(goal)?.and_then(|_| self.solution()))
   }
 }
@@ -0,0 +1,104 @@
+use super::{Search, SearchStats, Solution, State};
+use std::marker::PhantomData;
+
+/// What a search does when a state fails to be observed or expanded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ErrorPolicy {
+  /// Yield each error as an `Err` item, then carry on searching
+  #[default]
+  Propagate,
+  /// Drop each error and carry on searching, counting the errors dropped
+  Skip,
+  /// Yield the first error as an `Err` item, then end the search
+  Abort,
+}
+
+/// A search that handles errors according to an `ErrorPolicy`.
+///
+/// Every search reports errors as `Err` items, so this decides what is done
+/// with them. However errors are handled, `errors` gives the number that
+/// occurred, so a broken state transition is never hidden entirely.
+pub struct WithErrorPolicy<S: State, Q: Search<S>> {
+  search: Q,
+  policy: ErrorPolicy,
+  errors: usize,
+  aborted: bool,
+  _state: PhantomData<S>,
+}
+
+impl<S: State, Q: Search<S>> WithErrorPolicy<S, Q> {
+  /// Handles the errors of the given search with the given policy
+  pub fn new(search: Q, policy: ErrorPolicy) -> Self {
+    Self {
+      search,
+      policy,
+      errors: 0,
+      aborted: false,
+      _state: PhantomData,
+    }
+  }
+
+  /// The number of errors that have occurred, whether yielded or skipped
+  pub fn errors(&self) -> usize {
+    self.errors
+  }
+
+  /// Whether the search was ended by an error, under `ErrorPolicy::Abort`
+  pub fn aborted(&self) -> bool {
+    self.aborted
+  }
+
+  /// The policy errors are handled with
+  pub fn policy(&self) -> ErrorPolicy {
+    self.policy
+  }
+
+  /// Removes the policy, returning the underlying search
+  pub fn into_inner(self) -> Q {
+    self.search
+  }
+}
+
+impl<S: State, Q: Search<S>> Iterator for WithErrorPolicy<S, Q> {
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.aborted {
+      return None;
+    }
+    loop {
+      let e = match self.search.next()? {
+        Ok(state) => return Some(Ok(state)),
+        Err(e) => e,
+      };
+      self.errors += 1;
+      match self.policy {
+        ErrorPolicy::Propagate => return Some(Err(e)),
+        ErrorPolicy::Skip => continue,
+        ErrorPolicy::Abort => {
+          self.aborted = true;
+          return Some(Err(e));
+        }
+      }
+    }
+  }
+}
+
+impl<S: State, Q: Search<S>> Search<S> for WithErrorPolicy<S, Q> {
+  type Cost = Q::Cost;
+
+  /// Restarts the underlying search, resetting the error count.
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    self.errors = 0;
+    self.aborted = false;
+    self.search.restart_from(start)
+  }
+
+  fn solution(&self) -> Option<Solution<S, Q::Cost>> {
+    self.search.solution()
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    self.search.stats()
+  }
+}
@@ -0,0 +1,159 @@
+use super::{Search, SearchLimits, Solution, State};
+use std::{
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc,
+  },
+  thread,
+  time::{Duration, Instant},
+};
+
+/// A goal shared between the threads of a portfolio.
+type Goal<'g, S> = dyn Fn(&<S as State>::Observation) -> bool + Sync + 'g;
+
+/// The result of solving with one of the searches of a portfolio
+type Solved<S, C> = Result<Option<Solution<S, C>>, <S as State>::Error>;
+
+/// The best solution found by a portfolio, and the index of its search
+type Best<S, C> = Result<Option<(usize, Solution<S, C>)>, <S as State>::Error>;
+
+/// Builds a search from the start state and runs it until it finds a goal or
+/// reaches its limits.
+type Runner<'a, S, C> =
+  Box<dyn for<'g> FnOnce(S, &Goal<'g, S>, SearchLimits) -> Solved<S, C> + Send + 'a>;
+
+/// Runs several searches from the same start state at once, each on its own
+/// thread.
+///
+/// Which search is fastest often depends on the problem, so racing a few (such
+/// as `Astar` with different heuristics, `Greedy` and `Ida`) avoids having to
+/// pick one by hand. Every search must give solutions with the same type of
+/// cost, so compare path lengths (`usize`) when mixing searches with and
+/// without cost functions.
+///
+/// Searches are added as functions that build them from the start state,
+/// which are called on the search's own thread. Each search is wrapped in
+/// `Limited`, so that the others can be cancelled once they're no longer
+/// needed, and stopped at the deadline if one is set.
+///
+/// A search that fails stops without a solution, and doesn't stop the others.
+/// The first error is only returned if no search finds a solution.
+pub struct Portfolio<'a, S: State, C> {
+  start: S,
+  searches: Vec<Runner<'a, S, C>>,
+  deadline: Option<Instant>,
+}
+
+impl<'a, S: State, C> Portfolio<'a, S, C>
+where
+  S: Clone + Send + 'a,
+  S::Action: Send,
+  S::Error: Send,
+  C: Ord + Send,
+{
+  /// Creates a new portfolio with no searches, to be run from the given start
+  /// state.
+  pub fn new(start: S) -> Self {
+    Self {
+      start,
+      searches: vec![],
+      deadline: None,
+    }
+  }
+
+  /// Adds a search, given a function that builds it from the start state.
+  pub fn with_search<Q, F>(mut self, search: F) -> Self
+  where
+    Q: Search<S, Cost = C>,
+    F: FnOnce(S) -> Q + Send + 'a,
+  {
+    self.searches.push(Box::new(|start, goal, limits| {
+      search(start).limit(limits).solve(goal)
+    }));
+    self
+  }
+
+  /// Stops every search once the given time has passed
+  pub fn deadline(mut self, deadline: Instant) -> Self {
+    self.deadline = Some(deadline);
+    self
+  }
+
+  /// Stops every search once the given time has passed from now
+  pub fn timeout(self, timeout: Duration) -> Self {
+    self.deadline(Instant::now() + timeout)
+  }
+
+  /// The number of searches in the portfolio
+  pub fn len(&self) -> usize {
+    self.searches.len()
+  }
+
+  /// Returns whether there are no searches in the portfolio
+  pub fn is_empty(&self) -> bool {
+    self.searches.is_empty()
+  }
+
+  /// Returns the first solution found by any of the searches, along with the
+  /// index of the search that found it (in the order they were added).
+  ///
+  /// The other searches are cancelled as soon as a solution is found.
+  pub fn solve_first(self, goal: impl Fn(&S::Observation) -> bool + Sync) -> Best<S, C> {
+    self.run(&goal, true)
+  }
+
+  /// Returns the cheapest solution found by the searches before the deadline,
+  /// along with the index of the search that found it (in the order they were
+  /// added).
+  ///
+  /// Each search stops at its first solution. Without a deadline, this waits
+  /// for every search to finish.
+  pub fn solve_best(self, goal: impl Fn(&S::Observation) -> bool + Sync) -> Best<S, C> {
+    self.run(&goal, false)
+  }
+
+  /// Runs every search, cancelling the rest once one finds a solution if
+  /// `first` is set.
+  fn run(self, goal: &Goal<S>, first: bool) -> Best<S, C> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let mut limits = SearchLimits::new().cancel_with(cancel.clone());
+    limits.deadline = self.deadline;
+
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+      for (index, search) in self.searches.into_iter().enumerate() {
+        let (start, limits, sender) = (self.start.clone(), limits.clone(), sender.clone());
+        scope.spawn(move || {
+          let _ = sender.send((index, search(start, goal, limits)));
+        });
+      }
+      drop(sender);
+
+      let (mut best, mut error): (Option<(usize, Solution<S, C>)>, _) = (None, None);
+      for (index, solution) in receiver {
+        let solution = match solution {
+          Ok(Some(solution)) => solution,
+          Ok(None) => continue,
+          Err(e) => {
+            error.get_or_insert(e);
+            continue;
+          }
+        };
+        if best
+          .as_ref()
+          .is_none_or(|(i, best)| (&solution.cost, index) < (&best.cost, *i))
+        {
+          best = Some((index, solution));
+        }
+        if first {
+          cancel.store(true, Ordering::Relaxed);
+          break;
+        }
+      }
+      match (best, error) {
+        (None, Some(e)) => Err(e),
+        (best, _) => Ok(best),
+      }
+    })
+  }
+}
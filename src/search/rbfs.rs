@@ -0,0 +1,206 @@
+use super::{
+  helpers::{Estimate, FValue},
+  trail::{CostTrailState, Trail},
+  Search, SearchStats, Solution, State,
+};
+use crate::{
+  value::{SearchCost, SearchHeuristic},
+  wrappers::{PathCostState, StateWrapper},
+};
+use std::{collections::VecDeque, ops::Add};
+
+/// The successors of an expanded state, with their backed up f-values.
+struct Frame<S: State, C: SearchCost<S> + Clone, F> {
+  children: Vec<(CostTrailState<S, C>, Estimate<F>)>,
+  limit: Estimate<F>,
+  child: usize,
+}
+
+/// A Recursive best first traversal of possible states.
+///
+/// Explores states in the same order as `Astar`, but only stores the states on
+/// the current path and their siblings. Each sibling remembers the f-value of
+/// the best state below it that has been seen, so that when a cheaper sibling
+/// exists, the search can unwind and later return to the same point.
+///
+/// This means states will be explored multiple times, but the memory used only
+/// grows with the depth of the search. On top of this, no more than `budget`
+/// states are ever stored: as with `SmaStar`, a state whose successors would
+/// exceed the budget is given an infinite f-value, so the search only ends up
+/// below it once no other path is left. This keeps the search optimal whenever
+/// the optimal path and its siblings fit in memory.
+pub struct Rbfs<S: State, C: SearchCost<S>, H: SearchHeuristic<S>>
+where
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+{
+  start: S,
+  started: bool,
+  last: (Trail<S::Action>, C::Cost),
+  stack: Vec<Frame<S, C, FValue<S, C, H>>>,
+  errors: VecDeque<S::Error>,
+  action_cost: C,
+  heuristic: H,
+  budget: usize,
+  stats: SearchStats,
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Rbfs<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  /// Creates a new search that stores at most `budget` states
+  pub fn new(start: S, action_cost: C, heuristic: H, budget: usize) -> Self {
+    Self {
+      start,
+      started: false,
+      last: Default::default(),
+      stack: vec![],
+      errors: VecDeque::new(),
+      action_cost,
+      heuristic,
+      budget,
+      stats: SearchStats::default(),
+    }
+  }
+
+  /// The number of states stored on the stack
+  fn stored(&self) -> usize {
+    self.stack.iter().map(|frame| frame.children.len()).sum()
+  }
+
+  fn f_value(&self, state: &PathCostState<S, C>) -> Result<FValue<S, C, H>, S::Error> {
+    let value = self.heuristic.value(&state.observe()?);
+    Ok(<C::Cost as Add<H::Cost>>::add(state.path_cost(), value))
+  }
+
+  /// Expands the given state, with children inheriting its f-value if larger.
+  ///
+  /// If storing the children would exceed the budget, none are stored, so the
+  /// search unwinds with an infinite f-value for the state.
+  fn expand(
+    &mut self,
+    state: &CostTrailState<S, C>,
+    value: Estimate<FValue<S, C, H>>,
+    limit: Estimate<FValue<S, C, H>>,
+  ) {
+    self.last = (state.trail().clone(), state.state().path_cost());
+    let actions = state.actions().into_iter().collect::<Vec<_>>();
+    if self.stored() + actions.len() > self.budget {
+      self.stack.push(Frame {
+        children: vec![],
+        limit,
+        child: 0,
+      });
+      return;
+    }
+
+    self.stats.expand(self.stack.len());
+    let mut children = vec![];
+    for action in actions {
+      self.stats.generate();
+      let result = state.result(&action).map_err(S::Error::from);
+      match result.and_then(|child| Ok((self.f_value(child.state())?, child))) {
+        Ok((child_value, child)) => {
+          children.push((child, value.clone().max(Estimate::Finite(child_value))));
+        }
+        Err(e) => self.errors.push_back(e),
+      }
+    }
+    self.stack.push(Frame {
+      children,
+      limit,
+      child: 0,
+    });
+    self.stats.frontier(self.stored());
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Iterator for Rbfs<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if let Some(e) = self.errors.pop_front() {
+      return Some(Err(e));
+    }
+    if !self.started {
+      self.started = true;
+      let start = PathCostState::new(self.start.clone(), self.action_cost.clone());
+      match self.f_value(&start) {
+        Ok(value) => self.expand(&start.into(), Estimate::Finite(value), Estimate::Infinite),
+        Err(e) => return Some(Err(e)),
+      }
+      return Some(Ok(self.start.clone()));
+    }
+
+    loop {
+      let frame = self.stack.last_mut()?;
+      let mut order = (0..frame.children.len()).collect::<Vec<_>>();
+      order.sort_by(|&lhs, &rhs| frame.children[lhs].1.cmp(&frame.children[rhs].1));
+      let best = order.first().map(|&best| frame.children[best].1.clone());
+
+      // Unwind when no child is within the limit, remembering the best value.
+      // Children with an infinite value can't lead to a goal, even when the
+      // limit is infinite too.
+      let best = match best {
+        Some(value) if value != Estimate::Infinite && value <= frame.limit => order[0],
+        best => {
+          self.stack.pop();
+          let best = best.unwrap_or(Estimate::Infinite);
+          let parent = self.stack.last_mut()?;
+          parent.children[parent.child].1 = best;
+          continue;
+        }
+      };
+      let alternative = order.get(1).map(|&alt| frame.children[alt].1.clone());
+      let limit = alternative.map_or(frame.limit.clone(), |alt| alt.min(frame.limit.clone()));
+
+      frame.child = best;
+      let (state, value) = frame.children[best].clone();
+      self.expand(&state, value, limit);
+      return Some(Ok(state.unwrap().unwrap()));
+    }
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Search<S> for Rbfs<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  type Cost = C::Cost;
+
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    self.start = start;
+    self.started = false;
+    self.last = Default::default();
+    self.stack.clear();
+    self.errors.clear();
+    self.stats = SearchStats::default();
+    Ok(())
+  }
+
+  fn solution(&self) -> Option<Solution<S, C::Cost>> {
+    let (trail, cost) = self.last.clone();
+    let solution = Solution::new(self.start.clone(), trail.actions(), cost);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
+}
@@ -0,0 +1,410 @@
+use super::{
+  helpers::{Estimate, FValue},
+  trail::{CostTrailState, Trail},
+  Search, SearchStats, Solution, State,
+};
+use crate::{
+  value::{SearchCost, SearchHeuristic},
+  wrappers::{PathCostState, StateWrapper},
+};
+use std::{
+  cmp::Reverse,
+  collections::{BTreeSet, VecDeque},
+  ops::Add,
+};
+
+/// What is known about one successor of a state.
+enum Slot<F> {
+  /// The successor has not been generated yet
+  Unexplored,
+  /// The successor is stored in memory under the given id
+  InMemory(usize),
+  /// The successor was dropped to free memory, with its backed up f-value
+  Forgotten(Estimate<F>),
+}
+
+struct Node<S: State, C: SearchCost<S> + Clone, F> {
+  state: CostTrailState<S, C>,
+  parent: Option<(usize, usize)>,
+  depth: usize,
+  value: Estimate<F>,
+  /// The actions of the state, kept from when it was first expanded so that
+  /// its successors can be regenerated even if they change between calls
+  actions: Vec<S::Action>,
+  successors: Option<Vec<Slot<F>>>,
+  queued: bool,
+  yielded: bool,
+}
+
+type SmaNode<S, C, H> = Node<S, C, FValue<S, C, H>>;
+
+/// States that can still be expanded, by f-value then shallowest first
+type OpenKey<F> = (Estimate<F>, Reverse<usize>, usize);
+
+/// A Simplified memory bounded A* traversal of possible states.
+///
+/// Explores states in the same order as `Astar`, generating one successor at
+/// a time, but will never store more than `budget` states. When memory is full,
+/// the shallowest state with the highest f-value is forgotten, with its parent
+/// remembering its f-value so it can be regenerated if it becomes the best
+/// option again.
+///
+/// This is optimal whenever the optimal path fits in memory, i.e. has fewer
+/// than `budget` states. Paths that would exceed the budget are given an
+/// infinite f-value, so the search ends once no other path is left.
+pub struct SmaStar<S: State, C: SearchCost<S>, H: SearchHeuristic<S>>
+where
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+{
+  start: S,
+  last: (Trail<S::Action>, C::Cost),
+  nodes: Vec<Option<SmaNode<S, C, H>>>,
+  free: Vec<usize>,
+  states: BTreeSet<OpenKey<FValue<S, C, H>>>,
+  errors: VecDeque<S::Error>,
+  action_cost: C,
+  heuristic: H,
+  budget: usize,
+  stored: usize,
+  stats: SearchStats,
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> SmaStar<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  /// Creates a new search that stores at most `budget` states
+  pub fn new(start: S, action_cost: C, heuristic: H, budget: usize) -> Self {
+    let mut search = Self {
+      start: start.clone(),
+      last: Default::default(),
+      nodes: vec![],
+      free: vec![],
+      states: BTreeSet::new(),
+      errors: VecDeque::new(),
+      action_cost,
+      heuristic,
+      budget,
+      stored: 0,
+      stats: SearchStats::default(),
+    };
+    search.reset(start);
+    search
+  }
+
+  fn reset(&mut self, start: S) {
+    self.nodes.clear();
+    self.free.clear();
+    self.states.clear();
+    self.errors.clear();
+    self.stored = 0;
+    self.stats = SearchStats::default();
+    self.start = start.clone();
+    self.last = Default::default();
+
+    let start = PathCostState::new(start, self.action_cost.clone());
+    match self.f_value(&start) {
+      Ok(value) => _ = self.insert(start.into(), None, 0, value),
+      Err(e) => self.errors.push_back(e),
+    }
+  }
+
+  fn f_value(&self, state: &PathCostState<S, C>) -> Result<Estimate<FValue<S, C, H>>, S::Error> {
+    let value = self.heuristic.value(&state.observe()?);
+    let value = <C::Cost as Add<H::Cost>>::add(state.path_cost(), value);
+    Ok(Estimate::Finite(value))
+  }
+
+  /// The state stored under the given id, if it is still in memory.
+  ///
+  /// States are only forgotten once none of their successors are in memory,
+  /// and are removed from the queue when they are, so the ids held by parents
+  /// and the queue should always be found. An id that isn't is treated as
+  /// already forgotten.
+  fn node(&self, id: usize) -> Option<&SmaNode<S, C, H>> {
+    self.nodes.get(id)?.as_ref()
+  }
+
+  fn node_mut(&mut self, id: usize) -> Option<&mut SmaNode<S, C, H>> {
+    self.nodes.get_mut(id)?.as_mut()
+  }
+
+  fn insert(
+    &mut self,
+    state: CostTrailState<S, C>,
+    parent: Option<(usize, usize)>,
+    depth: usize,
+    value: Estimate<FValue<S, C, H>>,
+  ) -> usize {
+    let node = Node {
+      state,
+      parent,
+      depth,
+      value: value.clone(),
+      actions: vec![],
+      successors: None,
+      queued: true,
+      yielded: false,
+    };
+    let id = match self.free.pop() {
+      Some(id) => {
+        self.nodes[id] = Some(node);
+        id
+      }
+      None => {
+        self.nodes.push(Some(node));
+        self.nodes.len() - 1
+      }
+    };
+    self.states.insert((value, Reverse(depth), id));
+    self.stored += 1;
+    id
+  }
+
+  /// Drops the given state from memory, returning the id of its parent
+  fn forget(&mut self, id: usize) -> Option<usize> {
+    let node = self.nodes.get_mut(id)?.take()?;
+    self.free.push(id);
+    self.stored -= 1;
+    if node.queued {
+      self
+        .states
+        .remove(&(node.value.clone(), Reverse(node.depth), id));
+    }
+    let (parent, slot) = node.parent?;
+    let successors = self.node_mut(parent)?.successors.as_mut();
+    if let Some(successor) = successors.and_then(|successors| successors.get_mut(slot)) {
+      *successor = Slot::Forgotten(node.value);
+    }
+    Some(parent)
+  }
+
+  /// Forgets the shallowest leaf with the highest f-value, other than `keep`
+  fn forget_worst(&mut self, keep: usize) -> bool {
+    let worst = self.states.iter().rev().find_map(|&(_, _, id)| {
+      let node = self.node(id)?;
+      let successors = node.successors.iter().flatten();
+      let is_leaf = !successors
+        .into_iter()
+        .any(|slot| matches!(slot, Slot::InMemory(_)));
+      (id != keep && node.parent.is_some() && is_leaf).then_some(id)
+    });
+    let Some(parent) = worst.and_then(|id| self.forget(id)) else {
+      return false;
+    };
+    self.update(parent);
+    true
+  }
+
+  /// Updates the f-value of a state from its successors, and whether it can
+  /// still be expanded, then does the same for its parents.
+  ///
+  /// States that can no longer lead anywhere are forgotten.
+  fn update(&mut self, mut id: usize) {
+    loop {
+      let Some(node) = self.node(id) else {
+        return;
+      };
+      let (mut expandable, mut in_memory, mut explored) = (node.successors.is_none(), false, true);
+      let mut value = Estimate::Infinite;
+      for slot in node.successors.iter().flatten() {
+        let slot_value = match slot {
+          Slot::Unexplored => {
+            (expandable, explored) = (true, false);
+            continue;
+          }
+          Slot::InMemory(child) => match self.node(*child) {
+            Some(child) => {
+              in_memory = true;
+              child.value.clone()
+            }
+            None => Estimate::Infinite,
+          },
+          Slot::Forgotten(slot_value) => {
+            expandable |= slot_value != &Estimate::Infinite;
+            slot_value.clone()
+          }
+        };
+        value = value.min(slot_value);
+      }
+      let value = if node.successors.is_some() && explored {
+        value
+      } else {
+        node.value.clone()
+      };
+
+      let (old_value, depth, queued, parent) =
+        (node.value.clone(), node.depth, node.queued, node.parent);
+      if queued {
+        self.states.remove(&(old_value.clone(), Reverse(depth), id));
+      }
+      if expandable {
+        self.states.insert((value.clone(), Reverse(depth), id));
+      }
+      if let Some(node) = self.node_mut(id) {
+        node.value = value.clone();
+        node.queued = expandable;
+      }
+
+      let Some((parent, _)) = parent else {
+        return;
+      };
+      if !expandable && !in_memory {
+        self.forget(id);
+      } else if value == old_value {
+        return;
+      }
+      id = parent;
+    }
+  }
+
+  /// Generates the next successor of the given state.
+  fn expand(&mut self, id: usize) {
+    let Some(node) = self.nodes.get(id).and_then(Option::as_ref) else {
+      return;
+    };
+    // A path that can't fit in memory can never be expanded
+    if node.depth + 1 >= self.budget {
+      if let Some(node) = self.node_mut(id) {
+        node.successors = Some(vec![]);
+      }
+      return self.update(id);
+    }
+
+    let successors = match &node.successors {
+      Some(successors) => successors,
+      None => {
+        self.stats.expand(node.depth);
+        let actions = node.state.actions().into_iter().collect::<Vec<_>>();
+        let successors = actions.iter().map(|_| Slot::Unexplored).collect();
+        if let Some(node) = self.node_mut(id) {
+          node.actions = actions;
+          node.successors = Some(successors);
+        }
+        return self.update(id);
+      }
+    };
+
+    let unexplored = successors
+      .iter()
+      .position(|slot| matches!(slot, Slot::Unexplored));
+    let forgotten = successors
+      .iter()
+      .enumerate()
+      .filter_map(|(slot, successor)| match successor {
+        Slot::Forgotten(value @ Estimate::Finite(_)) => Some((value, slot)),
+        _ => None,
+      });
+    let Some(slot) = unexplored.or_else(|| forgotten.min().map(|(_, slot)| slot)) else {
+      return self.update(id);
+    };
+    let remembered = match &successors[slot] {
+      Slot::Forgotten(value) => value.clone(),
+      _ => Estimate::Infinite,
+    };
+
+    self.stats.generate();
+    let child = node
+      .state
+      .result(&node.actions[slot])
+      .map_err(S::Error::from);
+    let value = child.and_then(|child| {
+      let value = self.f_value(child.state())?;
+      Ok((child, value))
+    });
+    let (child, value) = match value {
+      Ok(child) => child,
+      Err(e) => {
+        self.errors.push_back(e);
+        if let Some(successors) = self.node_mut(id).and_then(|node| node.successors.as_mut()) {
+          successors[slot] = Slot::Forgotten(Estimate::Infinite);
+        }
+        return self.update(id);
+      }
+    };
+
+    // A regenerated state keeps the f-value it had when it was forgotten
+    let value = match remembered {
+      Estimate::Infinite => value,
+      remembered => value.max(remembered),
+    };
+    let value = value.max(node.value.clone());
+    let depth = node.depth + 1;
+    while self.stored >= self.budget && self.forget_worst(id) {}
+
+    let child = self.insert(child, Some((id, slot)), depth, value);
+    if let Some(successors) = self.node_mut(id).and_then(|node| node.successors.as_mut()) {
+      successors[slot] = Slot::InMemory(child);
+    }
+    self.stats.frontier(self.stored);
+    self.update(id);
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Iterator for SmaStar<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  type Item = Result<S, S::Error>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(e) = self.errors.pop_front() {
+        return Some(Err(e));
+      }
+
+      let (value, _, id) = self.states.first()?;
+      if value == &Estimate::Infinite {
+        return None;
+      }
+      let id = *id;
+
+      let Some(node) = self.node_mut(id) else {
+        self.states.pop_first();
+        continue;
+      };
+      if !node.yielded {
+        node.yielded = true;
+        let state = node.state.clone();
+        self.last = (state.trail().clone(), state.state().path_cost());
+        return Some(Ok(state.unwrap().unwrap()));
+      }
+      self.expand(id);
+    }
+  }
+}
+
+impl<S: State, C: SearchCost<S>, H: SearchHeuristic<S>> Search<S> for SmaStar<S, C, H>
+where
+  S: Clone,
+  S::Action: Clone,
+  C: Clone,
+  C::Cost: Add<H::Cost>,
+  <C::Cost as Add<H::Cost>>::Output: Ord + Clone,
+{
+  type Cost = C::Cost;
+
+  fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
+    self.reset(start);
+    Ok(())
+  }
+
+  fn solution(&self) -> Option<Solution<S, C::Cost>> {
+    let (trail, cost) = self.last.clone();
+    let solution = Solution::new(self.start.clone(), trail.actions(), cost);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
+}
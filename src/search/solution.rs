@@ -0,0 +1,75 @@
+use super::{SearchStats, State};
+
+/// A path found by a search, from its start state to a goal.
+#[derive(Clone, Debug)]
+pub struct Solution<S: State, C = usize> {
+  /// The state the search started from
+  pub start: S,
+  /// The actions taken from the start to reach the goal
+  pub actions: Vec<S::Action>,
+  /// The states reached after each action, if they have been recorded.
+  ///
+  /// These aren't kept by searches, so are only filled in by `with_states`.
+  pub states: Option<Vec<S>>,
+  /// The total cost of the actions taken.
+  ///
+  /// For searches that don't take a cost function, this is the number of
+  /// actions taken.
+  pub cost: C,
+  /// A lower bound on the cost of the optimal solution, for searches that
+  /// find one, such as `Ara`.
+  pub lower_bound: Option<C>,
+  /// Statistics on the search when the solution was found, if it collects them
+  pub stats: Option<SearchStats>,
+}
+
+impl<S: State, C> Solution<S, C> {
+  /// Creates a new solution, without the intermediate states or stats
+  pub fn new(start: S, actions: Vec<S::Action>, cost: C) -> Self {
+    Self {
+      start,
+      actions,
+      states: None,
+      cost,
+      lower_bound: None,
+      stats: None,
+    }
+  }
+
+  /// Sets the lower bound on the cost of the optimal solution
+  pub fn with_lower_bound(mut self, lower_bound: C) -> Self {
+    self.lower_bound = Some(lower_bound);
+    self
+  }
+
+  /// Sets the stats of the search that found this solution
+  pub fn with_stats(mut self, stats: Option<&SearchStats>) -> Self {
+    self.stats = stats.cloned();
+    self
+  }
+
+  /// Records the states reached along the path, by taking each action in turn
+  /// from the start state.
+  pub fn with_states(mut self) -> Result<Self, S::Error>
+  where
+    S: Clone,
+  {
+    let mut states: Vec<S> = Vec::with_capacity(self.actions.len());
+    for action in &self.actions {
+      let state = states.last().unwrap_or(&self.start).result(action)?;
+      states.push(state);
+    }
+    self.states = Some(states);
+    Ok(self)
+  }
+
+  /// The number of actions taken
+  pub fn len(&self) -> usize {
+    self.actions.len()
+  }
+
+  /// Whether the start state is the goal, i.e. no actions are taken
+  pub fn is_empty(&self) -> bool {
+    self.actions.is_empty()
+  }
+}
@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+
+/// Statistics on the work done by a search so far.
+///
+/// These are updated as the search is iterated, so can be read at any point
+/// to see how a search is progressing, or once it has finished to compare it
+/// against other searches (or the same search with a different heuristic).
+#[derive(Clone, Debug, Default)]
+pub struct SearchStats {
+  /// The number of states whose successors have been generated
+  pub expanded: usize,
+  /// The number of successors generated, including duplicates
+  pub generated: usize,
+  /// The number of successors dropped as a path at least as cheap was known
+  pub duplicates: usize,
+  /// The number of explored states queued again as a cheaper path was found
+  pub reopened: usize,
+  /// The largest number of states stored to be explored at once
+  pub peak_frontier: usize,
+  /// The deepest state that has been expanded
+  pub max_depth: usize,
+  /// The time between the first and the latest expansion
+  pub elapsed: Duration,
+  started: Option<Instant>,
+}
+
+impl SearchStats {
+  /// The average number of successors generated per expanded state
+  pub fn branching_factor(&self) -> f64 {
+    if self.expanded == 0 {
+      return 0.0;
+    }
+    self.generated as f64 / self.expanded as f64
+  }
+
+  /// The effective branching factor for a solution at the given depth.
+  ///
+  /// This is the branching factor `b` a uniform tree of the given depth would
+  /// need to contain the states generated, i.e. the solution to
+  /// `1 + generated = 1 + b + b^2 + ... + b^depth`. The closer this is to 1,
+  /// the better the heuristic used to guide the search.
+  pub fn effective_branching_factor(&self, depth: usize) -> Option<f64> {
+    if depth == 0 || self.generated == 0 {
+      return None;
+    }
+    let total = |b: f64| (1..=depth).map(|d| b.powi(d as i32)).sum::<f64>();
+    let target = self.generated as f64;
+    let (mut low, mut high) = (0.0, target.max(1.0));
+    for _ in 0..64 {
+      let mid = (low + high) / 2.0;
+      if total(mid) < target {
+        low = mid;
+      } else {
+        high = mid;
+      }
+    }
+    Some((low + high) / 2.0)
+  }
+
+  /// Records that a state at the given depth is being expanded
+  pub(crate) fn expand(&mut self, depth: usize) {
+    let now = Instant::now();
+    let started = *self.started.get_or_insert(now);
+    self.elapsed = now - started;
+    self.expanded += 1;
+    self.max_depth = self.max_depth.max(depth);
+  }
+
+  /// Records that a successor was generated
+  pub(crate) fn generate(&mut self) {
+    self.generated += 1;
+  }
+
+  /// Records that a generated successor was dropped as a duplicate
+  pub(crate) fn duplicate(&mut self) {
+    self.duplicates += 1;
+  }
+
+  /// Records that an explored state was queued again
+  pub(crate) fn reopen(&mut self) {
+    self.reopened += 1;
+  }
+
+  /// Records the current number of states stored to be explored
+  pub(crate) fn frontier(&mut self, size: usize) {
+    self.peak_frontier = self.peak_frontier.max(size);
+  }
+
+  /// Adds the work done by a search running alongside this one, such as on
+  /// another thread.
+  ///
+  /// The peak frontiers are summed, so this is an upper bound on the largest
+  /// number of states stored at once.
+  pub(crate) fn merge(&mut self, other: &SearchStats) {
+    self.expanded += other.expanded;
+    self.generated += other.generated;
+    self.duplicates += other.duplicates;
+    self.reopened += other.reopened;
+    self.peak_frontier += other.peak_frontier;
+    self.max_depth = self.max_depth.max(other.max_depth);
+    self.elapsed = self.elapsed.max(other.elapsed);
+    self.started = match (self.started, other.started) {
+      (Some(a), Some(b)) => Some(a.min(b)),
+      (a, b) => a.or(b),
+    };
+  }
+}
@@ -0,0 +1,151 @@
+use crate::{
+  state::State,
+  wrappers::{PathCostState, StateWrapper},
+};
+use derivative::Derivative;
+use std::sync::Arc;
+
+/// The last action taken along a `Trail`.
+struct Step<A> {
+  action: A,
+  depth: usize,
+  parent: Option<Arc<Step<A>>>,
+}
+
+impl<A> Drop for Step<A> {
+  /// Drops parents one at a time, as dropping a long trail recursively could
+  /// overflow the stack.
+  fn drop(&mut self) {
+    let mut parent = self.parent.take();
+    while let Some(step) = parent {
+      parent = match Arc::try_unwrap(step) {
+        Ok(mut step) => step.parent.take(),
+        Err(_) => None,
+      };
+    }
+  }
+}
+
+/// The actions taken from the start of a search to reach a state.
+///
+/// Trails are persistent lists, so successors of a state share all the steps
+/// leading up to it. This means storing a trail for every state in a frontier
+/// costs one step per state, rather than one per action taken.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Default(bound = ""))]
+pub(crate) struct Trail<A>(Option<Arc<Step<A>>>);
+
+impl<A> Trail<A> {
+  /// The number of actions taken
+  pub fn depth(&self) -> usize {
+    self.0.as_ref().map_or(0, |step| step.depth)
+  }
+
+  /// A new trail with the given action taken last
+  pub fn push(&self, action: A) -> Self {
+    Self(Some(Arc::new(Step {
+      action,
+      depth: self.depth() + 1,
+      parent: self.0.clone(),
+    })))
+  }
+
+  /// The actions taken, in order from the start
+  pub fn actions(&self) -> Vec<A>
+  where
+    A: Clone,
+  {
+    let mut actions = vec![];
+    let mut step = self.0.as_deref();
+    while let Some(Step { action, parent, .. }) = step {
+      actions.push(action.clone());
+      step = parent.as_deref();
+    }
+    actions.reverse();
+    actions
+  }
+}
+
+/// A state that tracks the actions taken to reach it, using a `Trail`.
+///
+/// This is how searches remember the path to the states they yield without
+/// storing a copy of the path for every state.
+#[derive(Derivative)]
+#[derivative(
+  Clone(bound = "S: Clone"),
+  PartialEq(bound = "S: PartialEq"),
+  Eq(bound = "S: Eq"),
+  Hash(bound = "S: std::hash::Hash")
+)]
+pub(crate) struct TrailState<S: State> {
+  state: S,
+  #[derivative(PartialEq = "ignore", Hash = "ignore")]
+  trail: Trail<S::Action>,
+}
+
+/// A state tracking both its path cost and the actions taken to reach it
+pub(crate) type CostTrailState<S, C> = TrailState<PathCostState<S, C>>;
+
+impl<S: State> From<S> for TrailState<S> {
+  fn from(state: S) -> Self {
+    Self {
+      state,
+      trail: Trail::default(),
+    }
+  }
+}
+
+impl<S: State> TrailState<S> {
+  /// The number of actions taken to reach this state
+  pub fn depth(&self) -> usize {
+    self.trail.depth()
+  }
+
+  pub fn trail(&self) -> &Trail<S::Action> {
+    &self.trail
+  }
+
+  pub fn state(&self) -> &S {
+    &self.state
+  }
+}
+
+impl<S: State> State for TrailState<S>
+where
+  S::Action: Clone,
+{
+  type Error = S::Error;
+  type Observation = S::Observation;
+  type Action = S::Action;
+  type ActionIter = S::ActionIter;
+  type ObserveError = S::ObserveError;
+  type ResultError = S::ResultError;
+
+  fn observe(&self) -> Result<Self::Observation, Self::ObserveError> {
+    self.state.observe()
+  }
+
+  fn actions(&self) -> Self::ActionIter {
+    self.state.actions()
+  }
+
+  fn result(&self, action: &Self::Action) -> Result<Self, Self::ResultError> {
+    self.state.result(action).map(|state| TrailState {
+      state,
+      trail: self.trail.push(action.clone()),
+    })
+  }
+}
+
+impl<S: State> StateWrapper<S> for TrailState<S>
+where
+  S::Action: Clone,
+{
+  fn unwrap(self) -> S {
+    self.state
+  }
+
+  fn replace(&mut self, state: S) -> S {
+    std::mem::replace(&mut self.state, state)
+  }
+}
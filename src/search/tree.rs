@@ -0,0 +1,91 @@
+use super::State;
+use std::{collections::HashMap, hash::Hash};
+
+/// A node in a `SearchTree`, describing how a state was reached.
+pub(crate) struct TreeNode<A, C> {
+  /// The id of the parent node and the action taken from it
+  pub parent: Option<(usize, A)>,
+  /// The cost of the best known path from the root
+  pub cost: C,
+  /// The number of actions taken from the root
+  pub depth: usize,
+}
+
+/// A record of all states reached by a search, and how they were reached.
+///
+/// Each state is stored once, alongside a link to its parent, so that the path
+/// to any state can be recovered without every state carrying its own history.
+pub(crate) struct SearchTree<S: State, C> {
+  ids: HashMap<S, usize>,
+  nodes: Vec<TreeNode<S::Action, C>>,
+}
+
+impl<S: State, C> SearchTree<S, C>
+where
+  S: Hash + Eq,
+{
+  pub fn new(root: S, cost: C) -> Self {
+    let mut tree = Self {
+      ids: HashMap::new(),
+      nodes: vec![],
+    };
+    tree.insert(root, None, cost);
+    tree
+  }
+
+  /// Removes all nodes, then inserts the given root
+  pub fn reset(&mut self, root: S, cost: C) {
+    self.ids.clear();
+    self.nodes.clear();
+    self.insert(root, None, cost);
+  }
+
+  /// The id of the given state, if it has been reached
+  pub fn id(&self, state: &S) -> Option<usize> {
+    self.ids.get(state).copied()
+  }
+
+  pub fn node(&self, id: usize) -> &TreeNode<S::Action, C> {
+    &self.nodes[id]
+  }
+
+  /// Records that `state` was reached from `parent` with the given cost.
+  ///
+  /// If the state has already been reached, its parent and cost are replaced.
+  pub fn insert(&mut self, state: S, parent: Option<(usize, S::Action)>, cost: C) -> usize {
+    let depth = parent
+      .as_ref()
+      .map_or(0, |(parent, _)| self.nodes[*parent].depth + 1);
+    let node = TreeNode {
+      parent,
+      cost,
+      depth,
+    };
+    match self.ids.get(&state) {
+      Some(&id) => {
+        self.nodes[id] = node;
+        id
+      }
+      None => {
+        let id = self.nodes.len();
+        self.ids.insert(state, id);
+        self.nodes.push(node);
+        id
+      }
+    }
+  }
+
+  /// The actions taken from the root to reach the given node
+  pub fn actions(&self, mut id: usize) -> Vec<S::Action>
+  where
+    S::Action: Clone,
+  {
+    let mut actions = vec![];
+    while let Some((parent, action)) = &self.nodes[id].parent {
+      actions.push(action.clone());
+      id = *parent;
+    }
+    actions.reverse();
+    actions
+  }
+}
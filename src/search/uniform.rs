@@ -1,72 +1,153 @@
-use super::{Search, State};
+use super::{
+  trail::{CostTrailState, Trail},
+  Search, SearchStats, Solution, State,
+};
 use crate::{
   value::SearchCost,
   wrappers::{PathCostState, StateWrapper},
 };
 use priority_queue::PriorityQueue;
-use std::{cmp::Reverse, hash::Hash};
+use std::{
+  cmp::Reverse,
+  collections::{HashMap, VecDeque},
+  hash::Hash,
+};
 
 /// A Uniform cost traversal of possible states.
 ///
 /// Will explore states with the lowest accumulated path cost first.
+///
+/// The best known path cost to each state is kept, and a state is only queued
+/// again when a cheaper path to it is found, reopening it if it has already
+/// been explored.
 pub struct Uniform<S: State, C: SearchCost<S>>
 where
   S: Hash + Eq,
+  C: Clone,
 {
-  states: PriorityQueue<PathCostState<S, C>, Reverse<C::Cost>>,
+  states: PriorityQueue<CostTrailState<S, C>, Reverse<C::Cost>>,
+  /// The best known path cost to each state, and whether it has been explored
+  costs: HashMap<S, (C::Cost, bool)>,
+  errors: VecDeque<S::Error>,
+  start: S,
+  last: (Trail<S::Action>, C::Cost),
+  stats: SearchStats,
   action_cost: C,
 }
 
 impl<S: State, C: SearchCost<S>> Uniform<S, C>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
   C: Clone,
 {
   pub fn new(start: S, action_cost: C) -> Self {
     let mut states = PriorityQueue::new();
+    let costs = HashMap::from([(start.clone(), Default::default())]);
     states.push(
-      PathCostState::new(start, action_cost.clone()),
+      PathCostState::new(start.clone(), action_cost.clone()).into(),
       Reverse(Default::default()),
     );
     Self {
       states,
+      costs,
+      errors: VecDeque::new(),
+      start,
+      last: Default::default(),
+      stats: SearchStats::default(),
       action_cost,
     }
   }
+
+  /// Records the path cost of a newly reached state, returning `false` if a
+  /// path at least as cheap is already known.
+  ///
+  /// Explored states are reopened when a cheaper path to them is found.
+  fn improve(&mut self, state: &PathCostState<S, C>) -> bool {
+    let (state, path_cost) = (state.clone().unwrap(), state.path_cost());
+    match self.costs.get(&state) {
+      Some((cost, _)) if cost <= &path_cost => {
+        self.stats.duplicate();
+        return false;
+      }
+      Some((_, true)) => self.stats.reopen(),
+      _ => {}
+    }
+    self.costs.insert(state, (path_cost, false));
+    true
+  }
 }
 
 impl<S: State, C: SearchCost<S>> Iterator for Uniform<S, C>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
   C: Clone,
 {
   type Item = Result<S, S::Error>;
 
   fn next(&mut self) -> Option<Self::Item> {
+    if let Some(e) = self.errors.pop_front() {
+      return Some(Err(e));
+    }
     let (state, _) = self.states.pop()?;
+    if let Some((_, explored)) = self.costs.get_mut(state.state().inner()) {
+      *explored = true;
+    }
 
-    let actions = state.actions().into_iter().filter_map(|action| {
-      let new_state = state.result(&action).ok()?;
-      let path_cost = new_state.path_cost();
-      Some((new_state, Reverse(path_cost)))
-    });
+    self.stats.expand(state.depth());
+    for action in state.actions() {
+      self.stats.generate();
+      let new_state = match state.result(&action) {
+        Ok(new_state) => new_state,
+        Err(e) => {
+          self.errors.push_back(e.into());
+          continue;
+        }
+      };
+      if !self.improve(new_state.state()) {
+        continue;
+      }
+      let path_cost = new_state.state().path_cost();
+      // Pushing an existing state would keep its old path cost
+      self.states.remove(&new_state);
+      self.states.push(new_state, Reverse(path_cost));
+    }
 
-    self.states.extend(actions);
-    Some(Ok(state.unwrap()))
+    self.stats.frontier(self.states.len());
+    self.last = (state.trail().clone(), state.state().path_cost());
+    Some(Ok(state.unwrap().unwrap()))
   }
 }
 
 impl<S: State, C: SearchCost<S>> Search<S> for Uniform<S, C>
 where
-  S: Hash + Eq,
+  S: Hash + Eq + Clone,
+  S::Action: Clone,
   C: Clone,
 {
+  type Cost = C::Cost;
+
   fn restart_from(&mut self, start: S) -> Result<(), S::Error> {
     self.states.clear();
+    self.errors.clear();
+    self.costs = HashMap::from([(start.clone(), Default::default())]);
     self.states.push(
-      PathCostState::new(start, self.action_cost.clone()),
+      PathCostState::new(start.clone(), self.action_cost.clone()).into(),
       Reverse(Default::default()),
     );
+    self.start = start;
+    self.last = Default::default();
+    self.stats = SearchStats::default();
     Ok(())
   }
+
+  fn solution(&self) -> Option<Solution<S, C::Cost>> {
+    let (trail, cost) = self.last.clone();
+    let solution = Solution::new(self.start.clone(), trail.actions(), cost);
+    Some(solution.with_stats(self.stats()))
+  }
+
+  fn stats(&self) -> Option<&SearchStats> {
+    Some(&self.stats)
+  }
 }
@@ -68,3 +68,95 @@ pub trait State: Sized {
   /// `clone`)
   fn result(&self, action: &Self::Action) -> Result<Self, Self::ResultError>;
 }
+
+/// A state whose actions can be reversed.
+///
+/// This allows for searching backwards from a known goal state, as is done in
+/// bidirectional search. For every action `a` in `state.predecessors()`, the
+/// state `prev = state.reverse(&a)` should satisfy `prev.result(&a) == state`.
+pub trait Predecessors: State {
+  /// A type for collections of Actions that lead to this state
+  ///
+  /// This represents all Actions that could have been taken to reach this state
+  type PredecessorIter: IntoIterator<Item = Self::Action>;
+
+  /// Returns all actions that could have been taken to reach this state
+  ///
+  /// This should be used with `reverse` to find the previous states
+  fn predecessors(&self) -> Self::PredecessorIter;
+
+  /// Takes an action backwards and transitions into the previous state
+  ///
+  /// This should produce a new state from a reference to this state (i.e. via
+  /// `clone`)
+  fn reverse(&self, action: &Self::Action) -> Result<Self, Self::ResultError>;
+}
+
+/// A state where the next action may be decided by chance, such as a dice roll
+/// or a card being drawn, rather than chosen by a player.
+///
+/// This allows games with random events to be searched with `Expectimax`.
+pub trait Chance: State {
+  /// A type for collections of the outcomes of a chance node
+  type OutcomeIter: IntoIterator<Item = (Self::Action, f64)>;
+
+  /// Returns each action that may be taken by chance with its probability, if
+  /// this state is a chance node.
+  ///
+  /// The probabilities should sum to 1. If this returns `None`, a player
+  /// chooses one of `actions` instead.
+  fn outcomes(&self) -> Option<Self::OutcomeIter>;
+}
+
+/// A state of a game, where players take turns choosing actions.
+///
+/// Players are numbered from 0, and need not take turns in a fixed order, so
+/// games with passes or extra turns can be searched.
+pub trait Game: State {
+  /// A type for the value of the game to a player
+  type Utility;
+
+  /// Returns the player whose turn it is to choose an action.
+  fn player(&self) -> usize;
+
+  /// Returns whether the game is over.
+  ///
+  /// A state that isn't terminal should have at least one action, so a player
+  /// that has to pass should be given an action for passing.
+  fn is_terminal(&self) -> bool;
+
+  /// Returns the value of this state to each player, indexed by player.
+  ///
+  /// This is only required to be meaningful for terminal states.
+  fn utility(&self) -> Vec<Self::Utility>;
+
+  /// Returns the state reached if the player to move passes the turn, even if
+  /// the rules don't allow it, for null-move pruning.
+  ///
+  /// By default, there is no null move, so nothing is pruned.
+  fn null_move(&self) -> Option<Self> {
+    None
+  }
+
+  /// Returns whether an action is noisy, such as a capture or a check, so
+  /// that the value of the state is likely to change a lot after it.
+  ///
+  /// A quiescence search keeps searching noisy actions past the depth limit,
+  /// until the state is quiet. By default, no action is noisy.
+  fn is_noisy(&self, action: &Self::Action) -> bool {
+    let _ = action;
+    false
+  }
+}
+
+/// A state with a Zobrist hash, so that states reached by more than one
+/// sequence of actions can be recognised.
+///
+/// The hash should be kept up to date by `result`, by xoring the keys of the
+/// features that change (see `ZobristKeys`), rather than computed from scratch.
+/// Equal states must have equal hashes, and states that differ should rarely
+/// share one.
+pub trait Zobrist: State {
+  /// Returns the hash of this state
+  fn zobrist(&self) -> u64;
+}
@@ -0,0 +1,19 @@
+use std::time::{Duration, Instant};
+
+/// Stats that are cleared at the start of each search, and record the time it
+/// took once it has finished.
+pub(crate) trait Timed: Default {
+  /// The time taken by the search
+  fn elapsed_mut(&mut self) -> &mut Duration;
+
+  /// Clears the stats for a new search, returning when it started
+  fn start(&mut self) -> Instant {
+    *self = Self::default();
+    Instant::now()
+  }
+
+  /// Records that a search which started at the given time has finished
+  fn finish(&mut self, started: Instant) {
+    *self.elapsed_mut() = started.elapsed();
+  }
+}
@@ -1 +1,6 @@
-pub use crate::{search::Search, state::State, value::SearchCost, value::SearchHeuristic};
+pub use crate::{
+  search::Search,
+  state::{Chance, Game, Predecessors, State, Zobrist},
+  value::SearchCost,
+  value::SearchHeuristic,
+};
@@ -6,6 +6,8 @@ mod cost;
 pub use cost::SearchCost;
 mod heuristic;
 pub use heuristic::SearchHeuristic;
+mod weighted;
+pub use weighted::Weighted;
 
 /// A wrapper around tuples to allow addition of costs
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
@@ -0,0 +1,32 @@
+use super::{SearchHeuristic, State};
+use std::ops::Mul;
+
+/// A heuristic scaled by a constant weight.
+///
+/// Using this as the heuristic for `Astar` gives weighted A*, which orders
+/// states by `g + w·h`. For `w > 1` this will expand far fewer states, but the
+/// path found may cost up to `w` times as much as the optimal path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Weighted<H, W> {
+  /// The heuristic to scale
+  pub heuristic: H,
+  /// The weight to multiply the heuristic's value by
+  pub weight: W,
+}
+
+impl<H, W> Weighted<H, W> {
+  /// Creates a new heuristic that scales `heuristic` by `weight`
+  pub fn new(heuristic: H, weight: W) -> Self {
+    Self { heuristic, weight }
+  }
+}
+
+impl<S: State, H: SearchHeuristic<S>, W: Clone> SearchHeuristic<S> for Weighted<H, W>
+where
+  H::Cost: Mul<W, Output = H::Cost>,
+{
+  type Cost = H::Cost;
+  fn value(&self, observed: &S::Observation) -> Self::Cost {
+    self.heuristic.value(observed) * self.weight.clone()
+  }
+}
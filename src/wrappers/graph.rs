@@ -1,14 +1,65 @@
 use super::{helpers::OptionIter, State, StateWrapper};
 use dashmap::DashSet;
 use derivative::Derivative;
-use std::{fmt::Display, hash::Hash, rc::Rc};
+use std::{
+  collections::HashSet,
+  fmt::{Debug, Display},
+  hash::{BuildHasher, Hash},
+  sync::{Arc, Mutex},
+};
+
+/// A set of the observations that have already been expanded, shared between
+/// every state of a `GraphState` search.
+///
+/// A search over a `GraphState` can be moved to other threads, or share its set
+/// with searches running at once, when the set is `Send + Sync`, as a
+/// `DashSet` or `Mutex<HashSet>` is when its observations are.
+pub trait ClosedSet<T> {
+  /// Adds an observation to the set, returning `false` if it was already there
+  fn insert(&self, item: T) -> bool;
+
+  /// Returns whether an observation is in the set
+  fn contains(&self, item: &T) -> bool;
+}
+
+impl<T: Hash + Eq, B: BuildHasher + Clone> ClosedSet<T> for DashSet<T, B> {
+  fn insert(&self, item: T) -> bool {
+    DashSet::insert(self, item)
+  }
+
+  fn contains(&self, item: &T) -> bool {
+    DashSet::contains(self, item)
+  }
+}
+
+impl<T: Hash + Eq, B: BuildHasher> ClosedSet<T> for Mutex<HashSet<T, B>> {
+  fn insert(&self, item: T) -> bool {
+    self.lock().unwrap().insert(item)
+  }
+
+  fn contains(&self, item: &T) -> bool {
+    self.lock().unwrap().contains(item)
+  }
+}
 
 /// A state that only expands if it has not been seen before.
 ///
 /// This is useful for graphs that have cycles and commonly repeated states.
-#[derive(Clone, Debug, Default, Derivative)]
-#[derivative(PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct GraphState<S: State>
+///
+/// Every state reached from the same start shares one `ClosedSet`, which is a
+/// `DashSet` unless another is given with `with_closed_set`.
+#[derive(Derivative)]
+#[derivative(
+  Clone(bound = "S: Clone"),
+  Debug(bound = "S: Debug, K: Debug"),
+  Default(bound = "S: Default, K: Default"),
+  PartialEq,
+  Eq,
+  PartialOrd,
+  Ord,
+  Hash
+)]
+pub struct GraphState<S: State, K = DashSet<<S as State>::Observation>>
 where
   S::Observation: Hash + Eq,
 {
@@ -19,7 +70,25 @@ where
     Ord = "ignore",
     Hash = "ignore"
   )]
-  seen: Rc<DashSet<S::Observation>>,
+  seen: Arc<K>,
+}
+
+impl<S: State, K: ClosedSet<S::Observation>> GraphState<S, K>
+where
+  S::Observation: Hash + Eq,
+{
+  /// Wraps a state, sharing the given set of observations already expanded.
+  ///
+  /// The set can be shared with other searches, or read once the search is
+  /// done.
+  pub fn with_closed_set(state: S, seen: Arc<K>) -> Self {
+    Self { state, seen }
+  }
+
+  /// The set of observations already expanded
+  pub fn closed_set(&self) -> &Arc<K> {
+    &self.seen
+  }
 }
 
 impl<S: State> From<S> for GraphState<S>
@@ -27,14 +96,11 @@ where
   S::Observation: Hash + Eq,
 {
   fn from(state: S) -> Self {
-    Self {
-      state,
-      seen: Rc::new(DashSet::new()),
-    }
+    Self::with_closed_set(state, Arc::new(DashSet::new()))
   }
 }
 
-impl<S: State + Display> Display for GraphState<S>
+impl<S: State + Display, K> Display for GraphState<S, K>
 where
   S::Observation: Hash + Eq,
 {
@@ -44,7 +110,7 @@ where
   }
 }
 
-impl<S: State> State for GraphState<S>
+impl<S: State, K: ClosedSet<S::Observation>> State for GraphState<S, K>
 where
   S::Observation: Hash + Eq,
 {
@@ -60,7 +126,7 @@ where
   type ActionIter = OptionIter<<S::ActionIter as IntoIterator>::IntoIter>;
   fn actions(&self) -> Self::ActionIter {
     // We only produce actions if we have not seen this state before
-    if self.observe().map_or(false, |obs| self.seen.insert(obs)) {
+    if self.observe().is_ok_and(|obs| self.seen.insert(obs)) {
       OptionIter::Some(self.state.actions().into_iter())
     } else {
       OptionIter::None
@@ -76,7 +142,7 @@ where
   }
 }
 
-impl<S: State> StateWrapper<S> for GraphState<S>
+impl<S: State, K: ClosedSet<S::Observation>> StateWrapper<S> for GraphState<S, K>
 where
   S::Observation: Hash + Eq,
 {
@@ -5,7 +5,7 @@ mod helpers;
 mod depth;
 pub use depth::DepthState;
 mod graph;
-pub use graph::GraphState;
+pub use graph::{ClosedSet, GraphState};
 mod pathcost;
 pub use pathcost::PathCostState;
 mod history;
@@ -36,6 +36,11 @@ impl<S: State, C: SearchCost<S>> PathCostState<S, C> {
   pub fn path_cost(&self) -> C::Cost {
     self.path_cost.clone()
   }
+
+  /// The wrapped state, without unwrapping it
+  pub(crate) fn inner(&self) -> &S {
+    &self.state
+  }
 }
 
 impl<S: State + Display, C: SearchCost<S>> Display for PathCostState<S, C>
@@ -0,0 +1,296 @@
+//! Checks that alpha-beta pruning finds the same values as minimax.
+
+use ai_search::{
+  games::{AlphaBeta, History, Killers, MiniMax, TranspositionTable},
+  prelude::*,
+};
+use proptest::{prelude::*, test_runner::FileFailurePersistence};
+use std::{
+  collections::hash_map::DefaultHasher,
+  hash::{Hash, Hasher},
+  ops::Bound,
+  time::Duration,
+};
+
+/// A game tree, where each state has a heuristic value and a player to move,
+/// so players may take extra turns.
+#[derive(Clone, Debug, Hash)]
+enum Tree {
+  Leaf(Vec<i32>),
+  Node(usize, i32, Vec<Tree>),
+}
+
+const PLAYERS: usize = 3;
+
+impl State for Tree {
+  type Error = ();
+  type Observation = i32;
+  type ObserveError = ();
+  fn observe(&self) -> Result<i32, ()> {
+    match self {
+      Tree::Leaf(utility) => Ok(utility[0]),
+      Tree::Node(_, value, _) => Ok(*value),
+    }
+  }
+
+  type Action = usize;
+  type ActionIter = std::ops::Range<usize>;
+  fn actions(&self) -> Self::ActionIter {
+    match self {
+      Tree::Leaf(_) => 0..0,
+      Tree::Node(_, _, children) => 0..children.len(),
+    }
+  }
+
+  type ResultError = ();
+  fn result(&self, action: &usize) -> Result<Self, ()> {
+    match self {
+      Tree::Leaf(_) => Err(()),
+      Tree::Node(_, _, children) => children.get(*action).cloned().ok_or(()),
+    }
+  }
+}
+
+impl Game for Tree {
+  type Utility = i32;
+
+  fn player(&self) -> usize {
+    match self {
+      Tree::Leaf(_) => 0,
+      Tree::Node(player, _, _) => *player,
+    }
+  }
+
+  fn is_terminal(&self) -> bool {
+    matches!(self, Tree::Leaf(_))
+  }
+
+  fn utility(&self) -> Vec<i32> {
+    match self {
+      Tree::Leaf(utility) => utility.clone(),
+      Tree::Node(..) => vec![],
+    }
+  }
+
+  fn null_move(&self) -> Option<Self> {
+    match self {
+      Tree::Leaf(_) => None,
+      Tree::Node(player, value, children) => {
+        Some(Tree::Node((player + 1) % PLAYERS, *value, children.clone()))
+      }
+    }
+  }
+
+  fn is_noisy(&self, action: &usize) -> bool {
+    action.is_multiple_of(2)
+  }
+}
+
+impl Zobrist for Tree {
+  fn zobrist(&self) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    self.hash(&mut hasher);
+    hasher.finish()
+  }
+}
+
+/// Small values, so that ties are common
+fn value() -> impl Strategy<Value = i32> {
+  -5..=5
+}
+
+fn tree() -> impl Strategy<Value = Tree> {
+  let leaf = prop::collection::vec(value(), PLAYERS).prop_map(Tree::Leaf);
+  leaf.prop_recursive(6, 256, 4, |inner| {
+    (0..PLAYERS, value(), prop::collection::vec(inner, 1..=4))
+      .prop_map(|(player, value, children)| Tree::Node(player, value, children))
+  })
+}
+
+/// The value of the state reached by following the actions, without searching
+/// any further.
+fn follow(tree: &Tree, actions: &[usize], player: usize) -> i32 {
+  let state = actions.iter().fold(tree.clone(), |state, action| {
+    state.result(action).expect("actions should be valid")
+  });
+  match state {
+    Tree::Leaf(utility) => utility[player],
+    Tree::Node(_, value, _) => value,
+  }
+}
+
+fn minimax(tree: &Tree, depth: usize) -> (Vec<usize>, i32) {
+  let mut minimax = MiniMax::new(|value: &i32| *value, depth);
+  minimax.minimax(tree.clone()).unwrap().unwrap()
+}
+
+fn alphabeta<R: std::ops::RangeBounds<i32>>(
+  tree: &Tree,
+  depth: usize,
+  window: R,
+) -> (Vec<usize>, i32) {
+  let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth);
+  alphabeta
+    .alphabeta_limits(tree.clone(), window)
+    .unwrap()
+    .unwrap()
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig {
+    failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+    ..ProptestConfig::default()
+  })]
+
+  #[test]
+  fn same_value_as_minimax(tree in tree(), depth in 0usize..8) {
+    let (_, expected) = minimax(&tree, depth);
+    let (actions, value) = alphabeta(&tree, depth, ..);
+    prop_assert_eq!(value, expected);
+    prop_assert!(actions.len() <= depth);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+  }
+
+  #[test]
+  fn visits_no_more_than_minimax(tree in tree(), depth in 0usize..8) {
+    let mut minimax = MiniMax::new(|value: &i32| *value, depth);
+    minimax.minimax(tree.clone()).unwrap().unwrap();
+    let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth);
+    alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert!(alphabeta.stats().visited <= minimax.stats().visited);
+  }
+
+  #[test]
+  fn fails_soft_outside_window(
+    tree in tree(),
+    depth in 0usize..8,
+    alpha in value(),
+    width in 0..=4,
+  ) {
+    let (_, expected) = minimax(&tree, depth);
+    let beta = alpha + width;
+    let window = (Bound::Excluded(alpha), Bound::Excluded(beta));
+    if width == 0 {
+      // Any value would be on both sides of an empty window
+      let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth);
+      prop_assert!(alphabeta.alphabeta_limits(tree.clone(), window).unwrap().is_none());
+      return Ok(());
+    }
+    let (actions, value) = alphabeta(&tree, depth, window);
+    if value <= alpha {
+      prop_assert!(expected <= value);
+    } else if value >= beta {
+      prop_assert!(expected >= value);
+    } else {
+      prop_assert_eq!(value, expected);
+      prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+    }
+  }
+
+  #[test]
+  fn exact_within_inclusive_window(tree in tree(), depth in 0usize..8) {
+    let (_, expected) = minimax(&tree, depth);
+    let (actions, value) = alphabeta(&tree, depth, expected..=expected);
+    prop_assert_eq!(value, expected);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+  }
+
+  #[test]
+  fn same_value_with_table(tree in tree(), capacity in 1usize..64) {
+    // Deep enough to reach every leaf, so transposed states have one value
+    let (_, expected) = minimax(&tree, 16);
+    let table = TranspositionTable::new(capacity);
+    let mut alphabeta = AlphaBeta::new(|value: &i32| *value, 16).with_table(table);
+    let (_, value) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    let (_, value) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+
+    let table = TranspositionTable::new(capacity);
+    let mut minimax = MiniMax::new(|value: &i32| *value, 16).with_table(table);
+    let (_, value) = minimax.minimax(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+  }
+
+  #[test]
+  fn iterative_deepening_same_as_minimax(tree in tree(), depth in 1usize..8) {
+    let (_, expected) = minimax(&tree, depth);
+    let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth);
+    let budget = Duration::from_secs(60);
+    let (actions, value) = alphabeta.iterative_deepening(tree.clone(), budget).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    prop_assert_eq!(alphabeta.stats().depth, depth);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+  }
+
+  #[test]
+  fn same_value_with_ordering(tree in tree(), depth in 0usize..8) {
+    let (_, expected) = minimax(&tree, depth);
+    let ordering = (History::new(), Killers::new());
+    let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth).with_ordering(ordering);
+    let (actions, value) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+
+    let reverse = |_: &Tree, actions: &mut [usize]| actions.reverse();
+    let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth).with_ordering(reverse);
+    let (actions, value) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+  }
+
+  #[test]
+  fn same_value_with_pvs(tree in tree(), depth in 1usize..8, delta in 0..=3) {
+    let (_, expected) = minimax(&tree, depth);
+    let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth).with_pvs();
+    let (actions, value) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+
+    let budget = Duration::from_secs(60);
+    let result = alphabeta.iterative_deepening_aspiration(tree.clone(), budget, delta);
+    let (actions, value) = result.unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+  }
+
+  #[test]
+  fn same_value_with_quiescence(tree in tree(), depth in 0usize..6, quiescence in 0usize..4) {
+    let mut minimax = MiniMax::new(|value: &i32| *value, depth).with_quiescence(quiescence);
+    let (_, expected) = minimax.minimax(tree.clone()).unwrap().unwrap();
+    let mut alphabeta =
+      AlphaBeta::new(|value: &i32| *value, depth).with_quiescence(quiescence);
+    let (actions, value) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    prop_assert!(actions.len() <= depth + quiescence);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+  }
+
+  #[test]
+  fn same_value_without_null_move_or_reductions(
+    tree in tree(),
+    depth in 1usize..8,
+    null in 0usize..3,
+    after in 1usize..4,
+    reduction in 1usize..3,
+  ) {
+    let (_, expected) = minimax(&tree, depth);
+    let mut alphabeta = AlphaBeta::new(|value: &i32| *value, depth)
+      .with_null_move(null)
+      .with_late_move_reductions(after, reduction);
+    let (actions, _) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert!(actions.len() <= depth);
+    let mut state = tree.clone();
+    for action in &actions {
+      let next = state.result(action);
+      prop_assert!(next.is_ok(), "{:?} is not an action of {:?}", action, state);
+      state = next.unwrap();
+    }
+
+    alphabeta.null_move = None;
+    alphabeta.late_move_reductions = None;
+    let (actions, value) = alphabeta.alphabeta(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(value, expected);
+    prop_assert_eq!(follow(&tree, &actions, tree.player()), expected);
+  }
+}
@@ -0,0 +1,140 @@
+//! Checks that expectimax finds the same values with and without *-minimax
+//! pruning.
+
+use ai_search::{games::Expectimax, prelude::*};
+use proptest::{prelude::*, test_runner::FileFailurePersistence};
+
+/// A game tree with chance nodes, where each state has a heuristic value.
+#[derive(Clone, Debug)]
+enum Tree {
+  Leaf(u32),
+  /// A terminal state with no utility
+  Missing,
+  Choice(usize, u32, Vec<Tree>),
+  /// Each child is equally likely
+  Chance(u32, Vec<Tree>),
+}
+
+const PLAYERS: usize = 2;
+
+/// The largest value of any state
+const MAX: u32 = 10;
+
+impl State for Tree {
+  type Error = ();
+  type Observation = u32;
+  type ObserveError = ();
+  fn observe(&self) -> Result<u32, ()> {
+    match self {
+      Tree::Leaf(value) | Tree::Choice(_, value, _) | Tree::Chance(value, _) => Ok(*value),
+      Tree::Missing => Ok(0),
+    }
+  }
+
+  type Action = usize;
+  type ActionIter = std::ops::Range<usize>;
+  fn actions(&self) -> Self::ActionIter {
+    match self {
+      Tree::Leaf(_) | Tree::Missing => 0..0,
+      Tree::Choice(_, _, children) | Tree::Chance(_, children) => 0..children.len(),
+    }
+  }
+
+  type ResultError = ();
+  fn result(&self, action: &usize) -> Result<Self, ()> {
+    match self {
+      Tree::Leaf(_) | Tree::Missing => Err(()),
+      Tree::Choice(_, _, children) | Tree::Chance(_, children) => {
+        children.get(*action).cloned().ok_or(())
+      }
+    }
+  }
+}
+
+impl Chance for Tree {
+  type OutcomeIter = Vec<(usize, f64)>;
+  fn outcomes(&self) -> Option<Vec<(usize, f64)>> {
+    match self {
+      Tree::Chance(_, children) => {
+        let probability = 1.0 / children.len() as f64;
+        Some((0..children.len()).map(|i| (i, probability)).collect())
+      }
+      _ => None,
+    }
+  }
+}
+
+impl Game for Tree {
+  type Utility = u32;
+
+  fn player(&self) -> usize {
+    match self {
+      Tree::Choice(player, _, _) => *player,
+      _ => 0,
+    }
+  }
+
+  fn is_terminal(&self) -> bool {
+    matches!(self, Tree::Leaf(_) | Tree::Missing)
+  }
+
+  fn utility(&self) -> Vec<u32> {
+    match self {
+      Tree::Leaf(value) => vec![*value; PLAYERS],
+      _ => vec![],
+    }
+  }
+}
+
+fn tree() -> impl Strategy<Value = Tree> {
+  let leaf = (0..=MAX).prop_map(Tree::Leaf);
+  leaf.prop_recursive(6, 256, 4, |inner| {
+    let children = prop::collection::vec(inner, 1..=4);
+    prop_oneof![
+      (0..PLAYERS, 0..=MAX, children.clone())
+        .prop_map(|(player, value, children)| Tree::Choice(player, value, children)),
+      (0..=MAX, children).prop_map(|(value, children)| Tree::Chance(value, children)),
+    ]
+  })
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig {
+    failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+    ..ProptestConfig::default()
+  })]
+
+  #[test]
+  fn same_value_with_star1_pruning(tree in tree(), depth in 0usize..8) {
+    let mut expectimax = Expectimax::new(|n: &u32| *n, depth);
+    let (_, expected) = expectimax.expectimax(tree.clone()).unwrap().unwrap();
+    let mut pruned = Expectimax::new(|n: &u32| *n, depth).with_bounds(0.0, MAX as f64);
+    let (_, value) = pruned.expectimax(tree.clone()).unwrap().unwrap();
+    prop_assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+    prop_assert!(pruned.stats().visited <= expectimax.stats().visited);
+  }
+}
+
+#[test]
+fn missing_utilities_are_skipped() {
+  // A chance node with a missing outcome has no value, so only the leaf is left
+  let tree = Tree::Choice(
+    0,
+    0,
+    vec![
+      Tree::Missing,
+      Tree::Chance(0, vec![Tree::Leaf(MAX), Tree::Missing]),
+      Tree::Leaf(3),
+    ],
+  );
+  let mut expectimax = Expectimax::new(|n: &u32| *n, 4);
+  assert_eq!(
+    expectimax.expectimax(tree.clone()).unwrap(),
+    Some((vec![2], 3.0))
+  );
+  let mut pruned = Expectimax::new(|n: &u32| *n, 4).with_bounds(0.0, MAX as f64);
+  assert_eq!(pruned.expectimax(tree).unwrap(), Some((vec![2], 3.0)));
+
+  let tree = Tree::Choice(1, 0, vec![Tree::Missing]);
+  assert_eq!(expectimax.expectimax(tree).unwrap(), None);
+}
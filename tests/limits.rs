@@ -0,0 +1,92 @@
+//! Checks that each of the `SearchLimits` stops a search, and that the limit
+//! responsible is reported.
+
+use ai_search::{prelude::*, search::Limited};
+use std::{
+  convert::Infallible,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  time::{Duration, Instant},
+};
+
+/// A counter that can be increased by 1 or 2 forever, so that only a limit
+/// stops searches over it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Counter(u32);
+
+impl State for Counter {
+  type Error = Infallible;
+  type Observation = u32;
+  type ObserveError = Infallible;
+  fn observe(&self) -> Result<u32, Infallible> {
+    Ok(self.0)
+  }
+
+  type Action = u32;
+  type ActionIter = [u32; 2];
+  fn actions(&self) -> [u32; 2] {
+    [1, 2]
+  }
+
+  type ResultError = Infallible;
+  fn result(&self, step: &u32) -> Result<Self, Infallible> {
+    Ok(Counter(self.0 + step))
+  }
+}
+
+fn bfs(limits: SearchLimits) -> Limited<Counter, Bfs<Counter>> {
+  Bfs::new(Counter(0)).limit(limits)
+}
+
+#[test]
+fn stops_after_max_expansions() {
+  let mut search = bfs(SearchLimits::new().expansions(10));
+  assert_eq!(search.by_ref().count(), 10);
+  assert_eq!(search.reached(), Some(Limit::Expansions));
+  assert_eq!(search.stats().unwrap().expanded, 10);
+  assert!(search.next().is_none());
+}
+
+#[test]
+fn stops_at_the_deadline() {
+  let mut search = bfs(SearchLimits::new().deadline(Instant::now()));
+  assert!(search.next().is_none());
+  assert_eq!(search.reached(), Some(Limit::Deadline));
+
+  let mut search = bfs(SearchLimits::new().timeout(Duration::from_millis(10)));
+  assert!(search.solve(|_| false).unwrap().is_none());
+  assert_eq!(search.reached(), Some(Limit::Deadline));
+}
+
+#[test]
+fn stops_once_over_the_memory_budget() {
+  let mut search = bfs(SearchLimits::new().memory(100));
+  assert!(search.solve(|_| false).unwrap().is_none());
+  assert_eq!(search.reached(), Some(Limit::Memory));
+  // The frontier only grows by 1 with each expansion
+  assert_eq!(search.stats().unwrap().peak_frontier, 101);
+}
+
+#[test]
+fn stops_once_cancelled() {
+  let cancel = Arc::new(AtomicBool::new(false));
+  let mut search = bfs(SearchLimits::new().cancel_with(cancel.clone()));
+  assert_eq!(search.by_ref().take(5).count(), 5);
+  assert_eq!(search.reached(), None);
+  cancel.store(true, Ordering::Relaxed);
+  assert!(search.next().is_none());
+  assert_eq!(search.reached(), Some(Limit::Cancelled));
+}
+
+#[test]
+fn restarting_clears_the_limit_reached() {
+  let mut search = bfs(SearchLimits::new().expansions(3));
+  assert_eq!(search.by_ref().count(), 3);
+  search.restart_from(Counter(10)).unwrap();
+  assert_eq!(search.reached(), None);
+  let goal = search.next_goal(|n| *n == 12).unwrap();
+  assert_eq!(goal, Some(Counter(12)));
+  assert_eq!(search.reached(), None);
+}
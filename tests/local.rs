@@ -0,0 +1,80 @@
+//! Checks that local searches escape a local optimum that traps hill climbing,
+//! and reach the known optimum of a small problem.
+
+use ai_search::{
+  local::{Genetic, HillClimbing, SimulatedAnnealing, TabuSearch},
+  prelude::*,
+};
+use std::convert::Infallible;
+
+/// A point on a line from 0 to `MAX`, that can be moved 1 either way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Point(u32);
+
+const MAX: u32 = 2 * RIDGE + 2;
+
+/// The top of the slope down to the local optimum at 0
+const RIDGE: u32 = 6;
+
+impl State for Point {
+  type Error = Infallible;
+  type Observation = u32;
+  type ObserveError = Infallible;
+  fn observe(&self) -> Result<u32, Infallible> {
+    Ok(self.0)
+  }
+
+  type Action = i32;
+  type ActionIter = Vec<i32>;
+  fn actions(&self) -> Vec<i32> {
+    [-1, 1]
+      .into_iter()
+      .filter(|step| (0..=MAX as i32).contains(&(self.0 as i32 + step)))
+      .collect()
+  }
+
+  type ResultError = Infallible;
+  fn result(&self, step: &i32) -> Result<Self, Infallible> {
+    Ok(Point(self.0.saturating_add_signed(*step)))
+  }
+}
+
+/// Slopes down from the ridge to a local optimum of 2 at 0, and to the optimum
+/// of 0 at `MAX`
+fn objective(x: &u32) -> u32 {
+  match *x {
+    x if x <= RIDGE => x + 2,
+    x => MAX - x,
+  }
+}
+
+#[test]
+fn hill_climbing_is_trapped() {
+  let mut search = HillClimbing::new(objective).seed(1);
+  assert_eq!(search.climb(Point(0)).unwrap(), (Point(0), 2));
+}
+
+#[test]
+fn simulated_annealing_reaches_the_optimum() {
+  let mut search = SimulatedAnnealing::new(objective, 10.0, 5000).seed(1);
+  assert_eq!(search.anneal(Point(0)).unwrap(), (Point(MAX), 0));
+  assert!(search.stats().steps > 0);
+}
+
+#[test]
+fn tabu_search_reaches_the_optimum() {
+  let mut search = TabuSearch::new(objective, 2, 100);
+  assert_eq!(search.search(Point(0)).unwrap(), (Point(MAX), 0));
+}
+
+#[test]
+fn genetic_reaches_the_optimum() {
+  let mut search = Genetic::new(objective, 50, 100).seed(1);
+  let population = search.random_walks(&Point(0), 2 * MAX as usize).unwrap();
+  let (walk, value) = search.evolve(&Point(0), population).unwrap().unwrap();
+  assert_eq!(value, 0);
+  let end = walk
+    .iter()
+    .fold(0, |x: u32, step| x.saturating_add_signed(*step));
+  assert_eq!(end, MAX);
+}
@@ -0,0 +1,151 @@
+//! Checks that max^n finds the same values with and without pruning.
+
+use ai_search::{
+  games::{MaxN, MiniMax},
+  prelude::*,
+};
+use proptest::{prelude::*, test_runner::FileFailurePersistence};
+
+/// A game tree, where each state has a value to each player and a player to
+/// move, so players may take extra turns.
+#[derive(Clone, Debug)]
+enum Tree {
+  Leaf(Vec<u32>),
+  Node(usize, Vec<u32>, Vec<Tree>),
+}
+
+const PLAYERS: usize = 3;
+
+/// The largest sum of the values of any state
+const MAX_SUM: u32 = 9;
+
+impl State for Tree {
+  type Error = ();
+  type Observation = Vec<u32>;
+  type ObserveError = ();
+  fn observe(&self) -> Result<Vec<u32>, ()> {
+    match self {
+      Tree::Leaf(values) | Tree::Node(_, values, _) => Ok(values.clone()),
+    }
+  }
+
+  type Action = usize;
+  type ActionIter = std::ops::Range<usize>;
+  fn actions(&self) -> Self::ActionIter {
+    match self {
+      Tree::Leaf(_) => 0..0,
+      Tree::Node(_, _, children) => 0..children.len(),
+    }
+  }
+
+  type ResultError = ();
+  fn result(&self, action: &usize) -> Result<Self, ()> {
+    match self {
+      Tree::Leaf(_) => Err(()),
+      Tree::Node(_, _, children) => children.get(*action).cloned().ok_or(()),
+    }
+  }
+}
+
+impl Game for Tree {
+  type Utility = u32;
+
+  fn player(&self) -> usize {
+    match self {
+      Tree::Leaf(_) => 0,
+      Tree::Node(player, _, _) => *player,
+    }
+  }
+
+  fn is_terminal(&self) -> bool {
+    matches!(self, Tree::Leaf(_))
+  }
+
+  fn utility(&self) -> Vec<u32> {
+    match self {
+      Tree::Leaf(values) => values.clone(),
+      Tree::Node(..) => vec![],
+    }
+  }
+}
+
+/// Small values, so that ties are common, which never sum to more than
+/// `MAX_SUM`
+fn values() -> impl Strategy<Value = Vec<u32>> {
+  prop::collection::vec(0..=MAX_SUM / PLAYERS as u32, PLAYERS)
+}
+
+fn tree() -> impl Strategy<Value = Tree> {
+  let leaf = values().prop_map(Tree::Leaf);
+  leaf.prop_recursive(6, 256, 4, |inner| {
+    (0..PLAYERS, values(), prop::collection::vec(inner, 1..=4))
+      .prop_map(|(player, values, children)| Tree::Node(player, values, children))
+  })
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig {
+    failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+    ..ProptestConfig::default()
+  })]
+
+  #[test]
+  fn same_result_with_shallow_pruning(tree in tree(), depth in 0usize..8) {
+    let mut maxn = MaxN::new(Vec::clone, depth);
+    let expected = maxn.maxn(tree.clone()).unwrap();
+    let mut pruned = MaxN::new(Vec::clone, depth).with_max_sum(MAX_SUM);
+    prop_assert_eq!(pruned.maxn(tree.clone()).unwrap(), expected);
+    prop_assert!(pruned.stats().visited <= maxn.stats().visited);
+  }
+
+  #[test]
+  fn paranoid_same_value_as_minimax(tree in tree(), depth in 0usize..8) {
+    let player = tree.player();
+    let mut minimax = MiniMax::new(|values: &Vec<u32>| values[player], depth);
+    let (_, expected) = minimax.minimax(tree.clone()).unwrap().unwrap();
+    let mut paranoid = MaxN::paranoid(Vec::clone, depth);
+    let (_, values) = paranoid.maxn(tree.clone()).unwrap().unwrap();
+    prop_assert_eq!(values[player], expected);
+  }
+}
+
+#[test]
+fn extra_turns_are_not_pruned() {
+  // Player 0 moves twice, so their first choice is no bound on their second
+  let tree = Tree::Node(
+    0,
+    vec![0, 0],
+    vec![
+      Tree::Leaf(vec![5, 5]),
+      Tree::Node(
+        0,
+        vec![0, 0],
+        vec![Tree::Leaf(vec![5, 5]), Tree::Leaf(vec![9, 1])],
+      ),
+    ],
+  );
+  let mut maxn = MaxN::new(Vec::clone, 4).with_max_sum(10);
+  let result = maxn.maxn(tree).unwrap();
+  assert_eq!(result, Some((vec![1, 1], vec![9, 1])));
+}
+
+#[test]
+fn actions_without_values_are_skipped() {
+  let tree = Tree::Node(
+    1,
+    vec![0, 0],
+    vec![
+      Tree::Leaf(vec![3]),
+      Tree::Leaf(vec![1, 2]),
+      Tree::Leaf(vec![]),
+    ],
+  );
+  for mut maxn in [MaxN::new(Vec::clone, 4), MaxN::paranoid(Vec::clone, 4)] {
+    let result = maxn.maxn(tree.clone()).unwrap();
+    assert_eq!(result, Some((vec![1], vec![1, 2])));
+  }
+
+  let tree = Tree::Node(1, vec![0, 0], vec![Tree::Leaf(vec![3])]);
+  let mut maxn = MaxN::new(Vec::clone, 4);
+  assert_eq!(maxn.maxn(tree).unwrap(), None);
+}
@@ -0,0 +1,153 @@
+//! Checks that Monte Carlo tree search stops at terminal states, scores them
+//! by their utility, and skips missing rewards.
+
+use ai_search::{
+  games::{Mcts, RandomRollout},
+  prelude::*,
+};
+use std::convert::Infallible;
+
+/// A game of Nim, where players take 1 or 2 stones in turn, and whoever takes
+/// the last stone wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Nim {
+  stones: u32,
+  player: usize,
+}
+
+impl State for Nim {
+  type Error = Infallible;
+  type Observation = u32;
+  type ObserveError = Infallible;
+  fn observe(&self) -> Result<u32, Infallible> {
+    Ok(self.stones)
+  }
+
+  type Action = u32;
+  type ActionIter = Vec<u32>;
+  fn actions(&self) -> Vec<u32> {
+    [1, 2].into_iter().filter(|n| *n <= self.stones).collect()
+  }
+
+  type ResultError = Infallible;
+  fn result(&self, n: &u32) -> Result<Self, Infallible> {
+    Ok(Nim {
+      stones: self.stones - n,
+      player: 1 - self.player,
+    })
+  }
+}
+
+impl Game for Nim {
+  type Utility = f64;
+
+  fn player(&self) -> usize {
+    self.player
+  }
+
+  fn is_terminal(&self) -> bool {
+    self.stones == 0
+  }
+
+  fn utility(&self) -> Vec<f64> {
+    // The player to move has nothing left to take, so the other player won
+    let mut utility = vec![1.0; 2];
+    utility[self.player] = 0.0;
+    utility
+  }
+}
+
+/// A one player game, counting up by 1 forever, which is over at `END` though
+/// there are still actions. Each count is worth its own size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Count(u32);
+
+const END: u32 = 3;
+
+/// The count that playouts give no reward for
+const MISSING: u32 = 2;
+
+impl State for Count {
+  type Error = Infallible;
+  type Observation = u32;
+  type ObserveError = Infallible;
+  fn observe(&self) -> Result<u32, Infallible> {
+    Ok(self.0)
+  }
+
+  type Action = u32;
+  type ActionIter = Vec<u32>;
+  fn actions(&self) -> Vec<u32> {
+    vec![1]
+  }
+
+  type ResultError = Infallible;
+  fn result(&self, n: &u32) -> Result<Self, Infallible> {
+    Ok(Count(self.0 + n))
+  }
+}
+
+impl Game for Count {
+  type Utility = f64;
+
+  fn player(&self) -> usize {
+    0
+  }
+
+  fn is_terminal(&self) -> bool {
+    self.0 >= END
+  }
+
+  fn utility(&self) -> Vec<f64> {
+    vec![self.0 as f64]
+  }
+}
+
+fn no_reward<O>(_: &O) -> Vec<f64> {
+  panic!("playouts should end at terminal states")
+}
+
+#[test]
+fn terminal_states_are_scored_by_their_utility() {
+  let mut mcts = Mcts::new(RandomRollout::new(no_reward), 100).seed(0);
+  let result = mcts.search(Count(0)).unwrap();
+  let best = result.best().expect("there should be an action");
+  assert_eq!(best.visits, result.iterations);
+  assert_eq!(best.value, END as f64);
+
+  // Playouts from the start stop at the end too
+  let mut mcts = Mcts::new(RandomRollout::new(no_reward), 1).seed(0);
+  let result = mcts.search(Count(0)).unwrap();
+  assert_eq!(result.best().unwrap().value, END as f64);
+}
+
+#[test]
+fn finds_the_winning_move() {
+  // Leaving a multiple of 3 stones wins, so the only winning move is to take 1
+  let start = Nim {
+    stones: 4,
+    player: 0,
+  };
+  let mut mcts = Mcts::new(RandomRollout::new(no_reward), 2000).seed(0);
+  let result = mcts.search(start).unwrap();
+  assert_eq!(result.best().unwrap().action, 1);
+}
+
+#[test]
+fn missing_rewards_are_skipped() {
+  let reward = |n: &u32| match *n {
+    MISSING => vec![],
+    n => vec![n as f64],
+  };
+  // The only action leads to a state with no reward, so it's dropped
+  let mut mcts = Mcts::new(RandomRollout::new(reward).max_depth(0), 10).seed(0);
+  let result = mcts.search(Count(MISSING - 1)).unwrap();
+  assert!(result.actions.is_empty());
+
+  // The action before it keeps the rewards of its own playouts
+  let mut mcts = Mcts::new(RandomRollout::new(reward).max_depth(0), 10).seed(0);
+  let result = mcts.search(Count(MISSING - 2)).unwrap();
+  let best = result.best().expect("there should be an action");
+  assert!(best.visits > 0);
+  assert_eq!(best.value, (MISSING - 1) as f64);
+}
@@ -0,0 +1,88 @@
+//! Checks how searches handle errors under each `ErrorPolicy`.
+
+use ai_search::prelude::*;
+
+/// A ladder that can be climbed 1 or 2 rungs at a time, up to `MAX`, with a
+/// broken rung at `BROKEN`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Ladder(u32);
+
+const MAX: u32 = 6;
+const BROKEN: u32 = 3;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Broken(u32);
+
+impl State for Ladder {
+  type Error = Broken;
+  type Observation = u32;
+  type ObserveError = Broken;
+  fn observe(&self) -> Result<u32, Broken> {
+    Ok(self.0)
+  }
+
+  type Action = u32;
+  type ActionIter = Vec<u32>;
+  fn actions(&self) -> Vec<u32> {
+    [1, 2]
+      .into_iter()
+      .filter(|step| self.0 + step <= MAX)
+      .collect()
+  }
+
+  type ResultError = Broken;
+  fn result(&self, step: &u32) -> Result<Self, Broken> {
+    match self.0 + step {
+      BROKEN => Err(Broken(BROKEN)),
+      n => Ok(Ladder(n)),
+    }
+  }
+}
+
+#[test]
+fn propagated_errors_are_yielded_and_searching_continues() {
+  let mut search = Bfs::new(Ladder(0)).on_error(ErrorPolicy::Propagate);
+  let items: Vec<_> = search.by_ref().collect();
+  let errors = items.iter().filter(|item| item.is_err()).count();
+  assert!(errors > 0);
+  assert_eq!(errors, search.errors());
+  assert!(items.contains(&Ok(Ladder(MAX))));
+  assert!(!search.aborted());
+
+  // Solving stops at the first error, before the goal is reached
+  let mut search = Bfs::new(Ladder(0)).on_error(ErrorPolicy::Propagate);
+  assert_eq!(search.solve(|n| *n == MAX).err(), Some(Broken(BROKEN)));
+}
+
+#[test]
+fn skipped_errors_are_counted() {
+  let mut search = Bfs::new(Ladder(0)).on_error(ErrorPolicy::Skip);
+  let solution = search
+    .solve(|n| *n == MAX)
+    .unwrap()
+    .expect("the goal should be found");
+  assert_eq!(solution.actions, [2, 2, 2]);
+  assert!(search.errors() > 0);
+
+  let mut search = Bfs::new(Ladder(0)).on_error(ErrorPolicy::Skip);
+  assert!(search.by_ref().all(|item| item.is_ok()));
+  assert!(search.errors() > 0);
+  assert!(!search.aborted());
+}
+
+#[test]
+fn aborting_ends_the_search_at_the_first_error() {
+  let mut search = Bfs::new(Ladder(0)).on_error(ErrorPolicy::Abort);
+  let items: Vec<_> = search.by_ref().collect();
+  assert_eq!(items.last(), Some(&Err(Broken(BROKEN))));
+  assert_eq!(items.iter().filter(|item| item.is_err()).count(), 1);
+  assert_eq!(search.errors(), 1);
+  assert!(search.aborted());
+  assert_eq!(search.next(), None);
+
+  // Restarting clears the abort
+  search.restart_from(Ladder(4)).unwrap();
+  assert!(!search.aborted());
+  assert_eq!(search.errors(), 0);
+  assert_eq!(search.solve(|n| *n == MAX).unwrap().unwrap().cost, 1);
+}
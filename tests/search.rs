@@ -0,0 +1,299 @@
+//! Checks the searches over a small graph with cycles.
+
+use ai_search::prelude::*;
+use std::{
+  convert::Infallible,
+  panic::{self, AssertUnwindSafe},
+  sync::mpsc,
+  thread,
+  time::Duration,
+};
+
+/// A position on a line, which can step 1 either way or jump 3 forwards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Walk(i32);
+
+const MAX: i32 = 30;
+
+impl State for Walk {
+  type Error = Infallible;
+  type Observation = i32;
+  type ObserveError = Infallible;
+  fn observe(&self) -> Result<i32, Infallible> {
+    Ok(self.0)
+  }
+
+  type Action = i32;
+  type ActionIter = Vec<i32>;
+  fn actions(&self) -> Vec<i32> {
+    [-1, 1, 3]
+      .into_iter()
+      .filter(|step| (0..=MAX).contains(&(self.0 + step)))
+      .collect()
+  }
+
+  type ResultError = Infallible;
+  fn result(&self, step: &i32) -> Result<Self, Infallible> {
+    Ok(Walk(self.0 + step))
+  }
+}
+
+impl Predecessors for Walk {
+  type PredecessorIter = Vec<i32>;
+  fn predecessors(&self) -> Vec<i32> {
+    [-1, 1, 3]
+      .into_iter()
+      .filter(|step| (0..=MAX).contains(&(self.0 - step)))
+      .collect()
+  }
+
+  fn reverse(&self, step: &i32) -> Result<Self, Infallible> {
+    Ok(Walk(self.0 - step))
+  }
+}
+
+/// Jumps cost 2, so they are cheaper per position moved than steps
+fn cost(step: &i32) -> u32 {
+  match step {
+    3 => 2,
+    _ => 1,
+  }
+}
+
+#[test]
+fn beam_backtracks_out_of_cycles() {
+  // The heuristic leads away from the goal, and every state has a successor,
+  // so only backtracking can find it
+  let heuristic = |n: &i32| (MAX - n) as u32;
+  let mut search = Beam::new(Walk(25), cost, heuristic, 1).with_backtracking();
+  let solution = search
+    .solve(|n| *n == 20)
+    .unwrap()
+    .expect("the goal should be found");
+  assert_eq!(solution.start, Walk(25));
+  assert_eq!(walk(25, &solution.actions), 20);
+}
+
+/// The position reached by taking each step in turn from `start`
+fn walk(start: i32, steps: &[i32]) -> i32 {
+  steps.iter().fold(start, |n, step| n + step)
+}
+
+/// An admissible heuristic, as no action moves more than 3 for a cost of 2
+fn towards(goal: i32) -> impl Fn(&i32) -> u32 + Clone {
+  move |n| (goal.abs_diff(*n) * 2) / 3
+}
+
+/// The cost of the cheapest path between two positions, found with `Astar`
+fn optimal(start: i32, goal: i32) -> u32 {
+  let mut search = Astar::new(Walk(start), cost, towards(goal));
+  let solution = search
+    .solve(|n| *n == goal)
+    .unwrap()
+    .expect("the goal should be found");
+  solution.cost
+}
+
+/// The start and goal positions searches are checked between, going both ways
+/// and over short and long distances
+const PAIRS: [(i32, i32); 4] = [(0, MAX), (MAX, 0), (7, 19), (12, 11)];
+
+/// Solves between each of `PAIRS` with the search built by `search` from the
+/// start and goal, checking that each solution is optimal and reaches the goal
+fn solve_optimally<Q>(search: impl Fn(i32, i32) -> Q) -> Vec<Solution<Walk, u32>>
+where
+  Q: Search<Walk, Cost = u32>,
+{
+  let solve = |(start, goal)| {
+    let solution = search(start, goal)
+      .solve(|n| *n == goal)
+      .unwrap()
+      .expect("the goal should be found");
+    assert_eq!(solution.cost, optimal(start, goal));
+    assert_eq!(walk(start, &solution.actions), goal);
+    solution
+  };
+  PAIRS.into_iter().map(solve).collect()
+}
+
+#[test]
+fn memory_bounded_searches_are_optimal_within_budget() {
+  let rbfs = solve_optimally(|start, goal| Rbfs::new(Walk(start), cost, towards(goal), 100));
+  let sma = solve_optimally(|start, goal| SmaStar::new(Walk(start), cost, towards(goal), 100));
+  for solution in rbfs.into_iter().chain(sma) {
+    assert!(solution.stats.unwrap().peak_frontier <= 100);
+  }
+}
+
+#[test]
+fn sma_star_regenerates_graph_states() {
+  // A graph state has no actions once it has been expanded, so they have to be
+  // kept to generate its successors one at a time
+  for budget in [12, 100] {
+    let start = GraphState::from(Walk(0));
+    let mut sma = SmaStar::new(start, cost, towards(MAX), budget);
+    let solution = sma
+      .solve(|n| *n == MAX)
+      .unwrap()
+      .expect("sma* should find the goal");
+    assert_eq!(solution.cost, optimal(0, MAX));
+  }
+}
+
+#[test]
+fn memory_bounded_searches_never_exceed_budget() {
+  for budget in 0..12 {
+    let mut rbfs = Rbfs::new(Walk(0), cost, towards(MAX), budget);
+    let _ = rbfs.solve(|n| *n == MAX);
+    assert!(rbfs.stats().unwrap().peak_frontier <= budget);
+
+    let mut sma = SmaStar::new(Walk(0), cost, towards(MAX), budget);
+    let _ = sma.solve(|n| *n == MAX);
+    assert!(sma.stats().unwrap().peak_frontier <= budget);
+  }
+}
+
+#[test]
+fn astar_reopens_states_with_inconsistent_heuristic() {
+  // Admissible, but far higher at 3 than at its neighbours, so 3's successors
+  // are first reached by more expensive paths around it
+  let heuristic = |n: &i32| if *n == 3 { 6 } else { 0 };
+  let mut search = Astar::new(Walk(0), cost, heuristic);
+  let solution = search
+    .solve(|n| *n == 12)
+    .unwrap()
+    .expect("the goal should be found");
+  assert_eq!(solution.cost, optimal(0, 12));
+  assert!(search.stats().unwrap().reopened > 0);
+}
+
+#[test]
+fn hda_is_optimal_on_many_threads() {
+  for (start, goal) in PAIRS {
+    let expected = optimal(start, goal);
+    for threads in [1, 2, 4] {
+      let mut search = Hda::new(Walk(start), cost, towards(goal)).threads(threads);
+      let solution = search
+        .solve(|n| *n == goal)
+        .unwrap()
+        .expect("the goal should be found");
+      assert_eq!(solution.cost, expected);
+      assert_eq!(walk(start, &solution.actions), goal);
+    }
+  }
+
+  // With no goal, every thread has to run out of states before they stop
+  let mut search = Hda::new(Walk(0), cost, |_: &i32| 0u32).threads(4);
+  assert!(search.solve(|n| *n > MAX).unwrap().is_none());
+}
+
+#[test]
+fn hda_resumes_panics_from_its_threads() {
+  // The search runs on another thread, so that a hang fails the test
+  let (sender, receiver) = mpsc::channel();
+  thread::spawn(move || {
+    let heuristic = |n: &i32| match n {
+      5 => panic!("the heuristic failed"),
+      _ => 0u32,
+    };
+    let mut search = Hda::new(Walk(0), cost, heuristic).threads(4);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| search.solve(|n| *n == MAX)));
+    let _ = sender.send(result.is_err());
+  });
+  let panicked = receiver
+    .recv_timeout(Duration::from_secs(10))
+    .expect("the search should stop");
+  assert!(panicked);
+}
+
+#[test]
+fn portfolio_cancels_the_losing_searches() {
+  // Greedy search has no closed set, so it is led back and forth between 29
+  // and 30 forever, and only stops once it's cancelled
+  let heuristic = |n: &i32| (MAX - n) as u32;
+  let (index, solution) = Portfolio::new(Walk(25))
+    .with_search(|start| Greedy::new(start, heuristic))
+    .with_search(Bfs::new)
+    .solve_first(|n| *n == 20)
+    .unwrap()
+    .expect("the goal should be found");
+  assert_eq!(index, 1);
+  assert_eq!(solution.actions, [-1; 5]);
+}
+
+#[test]
+fn portfolio_picks_the_cheapest_solution() {
+  let goal = 6;
+  // Leads the beam away from the jumps, which are the cheapest way to the goal
+  let heuristic = |n: &i32| match n % 3 {
+    0 if *n != goal => 100,
+    _ => goal.abs_diff(*n),
+  };
+  let beam = || Beam::new(Walk(0), cost, heuristic, 1);
+  let expected = optimal(0, goal);
+  assert!(beam().solve(|n| *n == goal).unwrap().unwrap().cost > expected);
+
+  let (index, solution) = Portfolio::new(Walk(0))
+    .with_search(|_| beam())
+    .with_search(|start| Astar::new(start, cost, towards(goal)))
+    .with_search(|start| Uniform::new(start, cost))
+    .solve_best(|n| *n == goal)
+    .unwrap()
+    .expect("the goal should be found");
+  // The last two tie, so the first of them wins
+  assert_eq!(index, 1);
+  assert_eq!(solution.cost, expected);
+}
+
+#[test]
+fn ida_is_optimal() {
+  solve_optimally(|start, goal| Ida::new(Walk(start), cost, towards(goal)));
+}
+
+#[test]
+fn bidirectional_searches_are_optimal() {
+  for (start, goal) in PAIRS.into_iter().chain([(5, 5)]) {
+    // Without a closed set, Bfs would search every path around the cycles
+    let shortest = Bfs::new(GraphState::from(Walk(start)))
+      .solve(|n| *n == goal)
+      .unwrap()
+      .expect("the goal should be found");
+    let mut bfs = BidirectionalBfs::new(Walk(start), Walk(goal));
+    let actions = bfs.find_path().expect("bfs should find the goal");
+    assert_eq!(actions.len(), shortest.actions.len());
+    assert_eq!(walk(start, &actions), goal);
+
+    let (heuristic, reverse) = (towards(goal), towards(start));
+    let mut astar = BidirectionalAstar::new(Walk(start), Walk(goal), cost, heuristic, reverse);
+    let (actions, cost) = astar.find_path().expect("a* should find the goal");
+    assert_eq!(cost, optimal(start, goal));
+    assert_eq!(walk(start, &actions), goal);
+  }
+}
+
+#[test]
+fn ara_improves_to_the_optimal_cost() {
+  for (start, goal) in PAIRS {
+    let expected = optimal(start, goal);
+    let weighted = Weighted::new(towards(goal), 3);
+    let solution = Astar::new(Walk(start), cost, weighted)
+      .solve(|n| *n == goal)
+      .unwrap()
+      .expect("weighted a* should find the goal");
+    assert!(solution.cost <= expected * 3);
+
+    let search = Ara::new(Walk(start), cost, towards(goal), [3, 2, 1], |n: &i32| {
+      *n == goal
+    });
+    let solutions = search.map(|solution| solution.unwrap()).collect::<Vec<_>>();
+    for (weight, solution) in &solutions {
+      assert!(solution.cost <= expected * weight);
+      assert!(solution.lower_bound.is_some_and(|bound| bound <= expected));
+      assert_eq!(walk(start, &solution.actions), goal);
+    }
+    let costs = solutions.iter().map(|(_, solution)| solution.cost);
+    assert!(costs.clone().zip(costs.skip(1)).all(|(a, b)| b <= a));
+    let (_, last) = solutions.last().expect("ara should find the goal");
+    assert_eq!((last.cost, last.lower_bound), (expected, Some(expected)));
+  }
+}
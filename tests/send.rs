@@ -0,0 +1,158 @@
+//! Checks that searches can be moved to, and share a closed set between,
+//! other threads.
+
+use ai_search::prelude::*;
+use std::{
+  collections::HashSet,
+  convert::Infallible,
+  rc::Rc,
+  sync::{Arc, Mutex},
+  thread,
+};
+
+/// A counter that can be increased by 1 or 2, up to `MAX`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Counter(u32);
+
+const MAX: u32 = 20;
+
+impl State for Counter {
+  type Error = Infallible;
+  type Observation = u32;
+  type ObserveError = Infallible;
+  fn observe(&self) -> Result<u32, Infallible> {
+    Ok(self.0)
+  }
+
+  type Action = u32;
+  type ActionIter = Vec<u32>;
+  fn actions(&self) -> Vec<u32> {
+    [1, 2]
+      .into_iter()
+      .filter(|step| self.0 + step <= MAX)
+      .collect()
+  }
+
+  type ResultError = Infallible;
+  fn result(&self, step: &u32) -> Result<Self, Infallible> {
+    Ok(Counter(self.0 + step))
+  }
+}
+
+impl Predecessors for Counter {
+  type PredecessorIter = Vec<u32>;
+  fn predecessors(&self) -> Vec<u32> {
+    [1, 2].into_iter().filter(|step| step <= &self.0).collect()
+  }
+
+  fn reverse(&self, step: &u32) -> Result<Self, Infallible> {
+    Ok(Counter(self.0 - step))
+  }
+}
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn searches_over_graph_states_are_send() {
+  let start = GraphState::from(Counter(0));
+  let cost = |_: &u32| 1u32;
+  let heuristic = |n: &u32| MAX - n;
+
+  assert_send(&Bfs::new(start.clone()));
+  assert_send(&Dfs::new(start.clone()));
+  assert_send(&Dls::new(start.clone(), 5));
+  assert_send(&Ids::new(start.clone()));
+  assert_send(&Uniform::new(start.clone(), cost));
+  assert_send(&Greedy::new(start.clone(), heuristic));
+  assert_send(&Beam::new(start.clone(), cost, heuristic, 4));
+  assert_send(&Astar::new(start.clone(), cost, heuristic));
+  assert_send(&Ara::new(
+    start.clone(),
+    cost,
+    heuristic,
+    [2, 1],
+    |n: &u32| *n == MAX,
+  ));
+  assert_send(&Ida::new(start.clone(), cost, heuristic));
+  assert_send(&Rbfs::new(start.clone(), cost, heuristic, 8));
+  assert_send(&SmaStar::new(start.clone(), cost, heuristic, 8));
+  assert_send(&Bfs::new(start.clone()).limit(SearchLimits::new()));
+  assert_send(&Bfs::new(start).on_error(ErrorPolicy::Skip));
+
+  // Bidirectional searches keep track of the states they've seen themselves
+  let (start, goal) = (Counter(0), Counter(MAX));
+  assert_send(&BidirectionalBfs::new(start, goal));
+  assert_send(&BidirectionalAstar::new(
+    start,
+    goal,
+    cost,
+    heuristic,
+    |n: &u32| *n,
+  ));
+}
+
+#[test]
+fn search_solves_on_another_thread() {
+  let mut search = Astar::new(
+    GraphState::from(Counter(0)),
+    |_: &u32| 1u32,
+    |n: &u32| (MAX - n) / 2,
+  );
+  let solution = thread::spawn(move || search.solve(|n| *n == MAX))
+    .join()
+    .unwrap()
+    .unwrap()
+    .expect("the goal should be reachable");
+  assert_eq!(solution.cost, MAX / 2);
+}
+
+#[test]
+fn closed_set_is_shared_between_threads() {
+  let seen = Arc::new(Mutex::new(HashSet::new()));
+  let searches = (0..4)
+    .map(|_| {
+      let start = GraphState::with_closed_set(Counter(0), seen.clone());
+      thread::spawn(move || Bfs::new(start).count())
+    })
+    .collect::<Vec<_>>();
+  for search in searches {
+    search.join().unwrap();
+  }
+
+  // Between them, the searches expanded every state once
+  let seen = seen.lock().unwrap();
+  assert_eq!(seen.len(), MAX as usize + 1);
+  assert!((0..=MAX).all(|n| seen.contains(&n)));
+}
+
+/// A counter observed through an `Rc`, which can't be shared between threads.
+#[derive(Clone, Debug)]
+struct Local(u32);
+
+impl State for Local {
+  type Error = Infallible;
+  type Observation = Rc<u32>;
+  type ObserveError = Infallible;
+  fn observe(&self) -> Result<Rc<u32>, Infallible> {
+    Ok(Rc::new(self.0))
+  }
+
+  type Action = u32;
+  type ActionIter = Vec<u32>;
+  fn actions(&self) -> Vec<u32> {
+    Counter(self.0).actions()
+  }
+
+  type ResultError = Infallible;
+  fn result(&self, step: &u32) -> Result<Self, Infallible> {
+    Ok(Local(self.0 + step))
+  }
+}
+
+#[test]
+fn graph_states_need_no_thread_safe_observations() {
+  let start = GraphState::from(Local(0));
+  let seen = start.closed_set().clone();
+  assert_eq!(Bfs::new(start).count(), MAX as usize * 2);
+  assert_eq!(seen.len(), MAX as usize + 1);
+}